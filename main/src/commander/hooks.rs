@@ -16,7 +16,7 @@ use crate::{
         combo_keys::{ComboKey, State},
         keys::Keys,
     },
-    configs::operations::get_mouse_read_state,
+    configs::{items::mouse::MouseConfig, operations::get_mouse_read_state},
     context::{Context, ContextAccessor},
     talent::{mouse::mouse_read, Talent},
 };
@@ -25,7 +25,7 @@ use std::{
     collections::HashMap,
     sync::{
         atomic::{AtomicBool, AtomicU32, Ordering},
-        Weak,
+        Arc, Weak,
     },
 };
 use win_wrap::{
@@ -121,6 +121,14 @@ pub(crate) fn set_keyboard_hook(context: Weak<Context>) -> WindowsHook {
                 // 保存最后按下的键
                 mng.set_last_pressed_key(&key);
 
+                // 按下任意键都停止连续朗读
+                if context.get_performer().get_say_all().stop() {
+                    let ctx2 = context.clone();
+                    context.get_work_runtime().spawn(async move {
+                        ctx2.get_performer().stop().await;
+                    });
+                }
+
                 if !key.is_modifierkey() {
                     // 键位按下时，检测组合热键是单机还是双击
                     let ck_single = ComboKey {
@@ -193,8 +201,15 @@ pub(crate) fn set_mouse_hook(context: Weak<Context>) -> WindowsHook {
     let old_point: (AtomicU32, AtomicU32) = (AtomicU32::new(0), AtomicU32::new(0));
     let context = context.clone();
 
+    // 鼠标移动非常频繁，不在钩子中读取配置，而是在配置改变时更新这个状态
+    let is_read = Arc::new(AtomicBool::new(get_mouse_read_state(context.clone())));
+    let is_read2 = is_read.clone();
+    context
+        .get_config_manager()
+        .subscribe(move |cfg: &MouseConfig| is_read2.store(cfg.is_read, Ordering::Release));
+
     WindowsHook::new(HOOK_TYPE_MOUSE_LL, move |w_param, l_param, next| {
-        if !get_mouse_read_state(context.clone()) || w_param.0 != WM_MOUSEMOVE as usize {
+        if !is_read.load(Ordering::Acquire) || w_param.0 != WM_MOUSEMOVE as usize {
             return next();
         }
 
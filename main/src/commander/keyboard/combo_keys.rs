@@ -14,9 +14,10 @@
 use std::{
     fmt::{Display, Formatter},
     hash::Hash,
+    str::FromStr,
 };
 
-use serde::{Deserialize, Serialize};
+use serde::{de::IntoDeserializer, Deserialize, Serialize};
 
 use crate::commander::keyboard::{keys::Keys, modify_keys::ModifierKeys};
 
@@ -58,7 +59,11 @@ impl Default for State {
     }
 }
 
+/**
+ * 组合键，在配置文件中保存为可读的字符串，例如"RigelA+Ctrl+Up"、"RigelA+F12 double"、"NumPad5 long"。
+ * */
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(into = "String", try_from = "String")]
 pub(crate) struct ComboKey {
     pub(crate) main_key: Keys,
     pub(crate) modify_keys: ModifierKeys,
@@ -81,6 +86,24 @@ impl ComboKey {
             state,
         }
     }
+
+    /**
+     * 获取在界面上显示的文字，按键名称使用当前的语言。
+     * */
+    pub(crate) fn get_text(&self) -> String {
+        let state = match self.state {
+            State::SinglePress => "",
+            State::DoublePress => "(Double)",
+            State::LongPress => "(Long)",
+            _ => "",
+        };
+        let modify = if self.modify_keys.is_empty() {
+            String::from("")
+        } else {
+            format!("{} +", self.modify_keys)
+        };
+        format!("{modify}{}{state}", self.main_key)
+    }
 }
 
 impl From<Vec<Keys>> for ComboKey {
@@ -106,20 +129,89 @@ impl Into<Vec<Keys>> for ComboKey {
     }
 }
 
+/// 修饰键在字符串中的名称，按照输出的顺序排列
+const MODIFIER_NAMES: [(ModifierKeys, &str); 5] = [
+    (ModifierKeys::RIGELA, "RigelA"),
+    (ModifierKeys::CTRL, "Ctrl"),
+    (ModifierKeys::ALT, "Alt"),
+    (ModifierKeys::SHIFT, "Shift"),
+    (ModifierKeys::WIN, "Win"),
+];
+
+/// 按键名称的前缀，字符串中省略这个前缀，例如Keys::VkF12写作"F12"
+const KEY_PREFIX: &str = "Vk";
+
 impl Display for ComboKey {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let state = match self.state {
-            State::SinglePress => "",
-            State::DoublePress => "(Double)",
-            State::LongPress => "(Long)",
-            _ => "",
-        };
-        let modify = if self.modify_keys.is_empty() {
-            String::from("")
-        } else {
-            format!("{} +", self.modify_keys)
+        for (modify, name) in MODIFIER_NAMES {
+            if self.modify_keys.contains(modify) {
+                write!(f, "{name}+")?;
+            }
+        }
+        let key = format!("{:?}", self.main_key);
+        write!(f, "{}", key.strip_prefix(KEY_PREFIX).unwrap_or(&key))?;
+        match self.state {
+            State::DoublePress => write!(f, " double"),
+            State::LongPress => write!(f, " long"),
+            _ => Ok(()),
+        }
+    }
+}
+
+//noinspection StructuralWrap
+/**
+ * 从字符串解析组合键，格式是用"+"连接的修饰键和主键，后面可以跟空格和"double"或"long"。
+ * 修饰键和状态不区分大小写，主键的名称区分大小写（例如NumPad5和Numpad5是不同的按键）。
+ * */
+impl FromStr for ComboKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (keys, state) = match s.rsplit_once(char::is_whitespace) {
+            Some((keys, state)) if state.eq_ignore_ascii_case("double") => {
+                (keys.trim_end(), State::DoublePress)
+            }
+            Some((keys, state)) if state.eq_ignore_ascii_case("long") => {
+                (keys.trim_end(), State::LongPress)
+            }
+            _ => (s, State::SinglePress),
         };
-        write!(f, "{modify}{}{state}", self.main_key)
+
+        let mut parts: Vec<&str> = keys.split('+').map(str::trim).collect();
+        let main = parts.pop().unwrap_or_default();
+        let mut modify_keys = ModifierKeys::empty();
+        for part in parts {
+            let Some((modify, _)) = MODIFIER_NAMES
+                .iter()
+                .find(|(_, name)| name.eq_ignore_ascii_case(part))
+            else {
+                return Err(format!("Unknown modifier key `{part}` in `{s}`."));
+            };
+            modify_keys |= *modify;
+        }
+
+        let main_key = Keys::deserialize(format!("{KEY_PREFIX}{main}").into_deserializer())
+            .map_err(|_: serde::de::value::Error| format!("Unknown key `{main}` in `{s}`."))?;
+        if main_key == Keys::VkNone || main_key.is_modifierkey() {
+            return Err(format!("`{main}` can't be used as the main key in `{s}`."));
+        }
+
+        Ok(ComboKey::new(main_key, modify_keys, state))
+    }
+}
+
+impl From<ComboKey> for String {
+    fn from(value: ComboKey) -> Self {
+        value.to_string()
+    }
+}
+
+impl TryFrom<String> for ComboKey {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
     }
 }
 
@@ -139,3 +231,67 @@ impl From<ComboKey> for ComboKeyExt {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combo_key_to_str() {
+        let key = ComboKey::new(
+            Keys::VkUp,
+            ModifierKeys::CTRL | ModifierKeys::RIGELA,
+            State::SinglePress,
+        );
+        assert_eq!(key.to_string(), "RigelA+Ctrl+Up");
+        let key = ComboKey::new(Keys::VkF12, ModifierKeys::RIGELA, State::DoublePress);
+        assert_eq!(key.to_string(), "RigelA+F12 double");
+        let key = ComboKey::new(Keys::VkNumPad5, ModifierKeys::empty(), State::LongPress);
+        assert_eq!(key.to_string(), "NumPad5 long");
+    }
+
+    #[test]
+    fn test_combo_key_from_str() {
+        for s in [
+            "RigelA+Ctrl+Up",
+            "RigelA+F12 double",
+            "NumPad5 long",
+            "Numpad5",
+        ] {
+            assert_eq!(s.parse::<ComboKey>().unwrap().to_string(), s);
+        }
+
+        // 修饰键和状态不区分大小写，可以有多余的空格
+        let key: ComboKey = " ctrl + rigela + Up  DOUBLE ".parse().unwrap();
+        assert_eq!(
+            key,
+            ComboKey::new(
+                Keys::VkUp,
+                ModifierKeys::RIGELA | ModifierKeys::CTRL,
+                State::DoublePress
+            )
+        );
+
+        assert!("RigelA+".parse::<ComboKey>().is_err());
+        assert!("Hyper+Up".parse::<ComboKey>().is_err());
+        assert!("RigelA+Foo".parse::<ComboKey>().is_err());
+        assert!("RigelA+Ctrl".parse::<ComboKey>().is_err());
+        assert!("numpad5".parse::<ComboKey>().is_err());
+    }
+
+    #[test]
+    fn test_combo_key_serde() {
+        #[derive(Serialize, Deserialize)]
+        struct Config {
+            key: ComboKey,
+        }
+
+        let config = Config {
+            key: ComboKey::new(Keys::VkF12, ModifierKeys::RIGELA, State::DoublePress),
+        };
+        let text = toml::to_string(&config).unwrap();
+        assert_eq!(text.trim(), r#"key = "RigelA+F12 double""#);
+        assert_eq!(toml::from_str::<Config>(&text).unwrap().key, config.key);
+        assert!(toml::from_str::<Config>(r#"key = "RigelA+Foo""#).is_err());
+    }
+}
@@ -12,47 +12,168 @@
  */
 
 pub(crate) mod items;
+mod migration;
 pub(crate) mod operations;
+mod profile;
+pub(crate) mod validation;
 
+use crate::configs::items::app_profile::AppProfilesConfig;
 use crate::configs::items::general::GeneralConfig;
 use crate::configs::items::hotkeys::HotKeysConfig;
 use crate::configs::items::mouse::MouseConfig;
 use crate::configs::items::navigation::NavigationConfig;
+use crate::configs::items::sound::SoundConfig;
+use crate::configs::items::speech_dict::SpeechDictConfig;
 use crate::configs::items::tts::TtsConfig;
+use crate::configs::items::voice_profile::VoiceProfilesConfig;
+use crate::configs::migration::{migrate, CONFIG_VERSION};
+use crate::configs::profile::{apply_profile, split_profile};
+use crate::configs::validation::{merge_unknown, parse_lenient, ConfigProblem, Validate};
 use arc_swap::ArcSwap;
+use chrono::Local;
 use log::error as err_log;
-use log::info;
+use log::{info, warn};
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use std::fs::{read_to_string, File};
+use std::fmt::{Debug, Formatter};
+use std::fs::{copy, metadata, read_to_string, remove_file, rename, File};
 use std::io::Write;
 use std::ops::Deref;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::thread::{sleep, spawn};
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
+use tokio::runtime::Runtime;
+use tokio::sync::Notify;
+use tokio::task::spawn_blocking;
+use tokio::time::{interval, timeout};
+use toml::Table;
 
-/// 配置项目的根元素
-#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+/// 配置项目的根元素，缺少的配置项使用默认值，所以加入新的配置项不会导致旧的配置文件无法读取
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub(crate) struct ConfigRoot {
+    /// 配置文件的版本，读取旧版本的配置文件时会先进行迁移
+    #[serde(default)]
+    pub(crate) version: u32,
+    #[serde(default)]
     pub(crate) tts_config: TtsConfig,
+    #[serde(default)]
     pub(crate) mouse_config: MouseConfig,
+    #[serde(default)]
     pub(crate) hotkeys_config: HotKeysConfig,
+    #[serde(default)]
     pub(crate) general_config: GeneralConfig,
+    #[serde(default)]
     pub(crate) navigation_config: NavigationConfig,
+    #[serde(default)]
+    pub(crate) speech_dict_config: SpeechDictConfig,
+    #[serde(default)]
+    pub(crate) voice_profiles_config: VoiceProfilesConfig,
+    #[serde(default)]
+    pub(crate) sound_config: SoundConfig,
+    #[serde(default)]
+    pub(crate) app_profiles_config: AppProfilesConfig,
 }
 
+impl Default for ConfigRoot {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            tts_config: Default::default(),
+            mouse_config: Default::default(),
+            hotkeys_config: Default::default(),
+            general_config: Default::default(),
+            navigation_config: Default::default(),
+            speech_dict_config: Default::default(),
+            voice_profiles_config: Default::default(),
+            sound_config: Default::default(),
+            app_profiles_config: Default::default(),
+        }
+    }
+}
+
+//noinspection StructuralWrap
+/**
+ * 可以订阅修改的配置部分，用于只在关心的配置改变时得到通知。
+ * */
+pub(crate) trait ConfigSection: PartialEq + Send + Sync + 'static {
+    /**
+     * 从根元素中获取这一部分配置。
+     * `config` 配置的根元素。
+     * */
+    fn get_section(config: &ConfigRoot) -> &Self;
+}
+
+macro_rules! impl_config_section {
+    ($($ty:ty => $field:ident),* $(,)?) => {
+        $(
+            impl ConfigSection for $ty {
+                fn get_section(config: &ConfigRoot) -> &Self {
+                    &config.$field
+                }
+            }
+        )*
+    };
+}
+
+impl_config_section! {
+    TtsConfig => tts_config,
+    MouseConfig => mouse_config,
+    HotKeysConfig => hotkeys_config,
+    GeneralConfig => general_config,
+    NavigationConfig => navigation_config,
+    SpeechDictConfig => speech_dict_config,
+}
+
+/// 配置改变时的监听器，参数是改变前和改变后的配置
+type ConfigListener = Arc<dyn Fn(&ConfigRoot, &ConfigRoot) + Send + Sync>;
+
+/// 检查配置文件是否被外部修改的间隔
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 配置修改后延时写入的时间，这段时间内的多次修改只写入一次
+const WRITE_DELAY: Duration = Duration::from_secs(3);
+
+/// 保留的配置文件备份数量，备份的文件名是config.toml.1（最新）到config.toml.N（最旧）
+const BACKUP_COUNT: usize = 3;
+
 /// 配置管理器
-#[derive(Debug)]
 pub(crate) struct ConfigManager {
     // 配置文件的路径
     pub(crate) path: PathBuf,
-    // 当前的配置
+    // 当前的配置，不包含应用程序的配置
     config: Arc<ArcSwap<ConfigRoot>>,
-    // 更新配置的操作时间,不要求过于精确，时间戳就可以，Instant是纳秒级别的
-    update_time: Arc<AtomicU64>,
-    // 延时写入是否完成
-    write_finished: Arc<AtomicBool>,
+    // 合并了当前应用程序配置的配置
+    merged: ArcSwap<ConfigRoot>,
+    // 当前应用程序配置的名称
+    profile: Mutex<Option<String>>,
+    // 前景窗口的应用程序的进程名称
+    app: Mutex<Option<String>>,
+    // 是否有还没有写入配置文件的修改
+    dirty: AtomicBool,
+    // 配置修改时通知延时写入的任务
+    write_notify: Arc<Notify>,
+    // 防止同时写入配置文件
+    write_lock: Mutex<()>,
+    // 配置改变时的监听器
+    listeners: Mutex<Vec<ConfigListener>>,
+    // 最后一次读取或写入时配置文件的修改时间，用于发现外部的修改
+    file_time: Mutex<Option<SystemTime>>,
+    // 是否已经启动了后台任务
+    started: AtomicBool,
+    // 配置文件中程序不认识的项目，写入时保留这些项目
+    unknown: Mutex<Table>,
+    // 读取配置文件时发现的问题
+    problems: Mutex<Vec<ConfigProblem>>,
+}
+
+impl Debug for ConfigManager {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfigManager")
+            .field("path", &self.path)
+            .field("profile", &self.profile.lock())
+            .finish()
+    }
 }
 
 impl ConfigManager {
@@ -63,14 +184,25 @@ impl ConfigManager {
         Self {
             path,
             config: Default::default(),
-            update_time: AtomicU64::new(0).into(),
-            write_finished: AtomicBool::new(true).into(),
+            merged: Default::default(),
+            profile: None.into(),
+            app: None.into(),
+            dirty: AtomicBool::new(false),
+            write_notify: Notify::new().into(),
+            write_lock: ().into(),
+            listeners: vec![].into(),
+            file_time: None.into(),
+            started: AtomicBool::new(false),
+            unknown: Table::new().into(),
+            problems: vec![].into(),
         }
     }
 
     /// 初始化当前配置，从配置文件获取配置信息
     pub(crate) fn apply(&self) {
         self.config.store(Arc::new(self.read()));
+        *self.file_time.lock() = _get_file_time(&self.path);
+        self.update_merged();
 
         // 设置当前程序显示语言
         let lang: String = self.get_config().general_config.lang.clone().into();
@@ -78,98 +210,444 @@ impl ConfigManager {
         info!("The current locale of the user is {lang}.");
     }
 
-    /// 获取当前的配置
+    /// 获取当前的配置，如果前景窗口的应用程序有自己的配置，则返回合并后的配置
     pub(crate) fn get_config(&self) -> ConfigRoot {
-        self.config.load().deref().deref().clone()
+        self.merged.load().deref().deref().clone()
     }
 
-    /// 修改当前的配置，修改完写入配置文件
+    //noinspection StructuralWrap
+    /**
+     * 修改当前的配置，修改完延时写入配置文件。
+     * 使用应用程序的配置时，应用程序配置覆盖的项目写回应用程序的配置，其他的项目写入基础配置。
+     * `config` 修改后的配置。
+     * */
     pub(crate) fn set_config(&self, config: &ConfigRoot) {
-        self.config.store(Arc::new(config.clone()));
+        let base = match self.profile.lock().clone() {
+            None => config.clone(),
+            Some(name) => match split_profile(config, &self.config.load(), &name) {
+                Some(base) => base,
+                None => {
+                    // 直接使用合并后的配置会把应用程序配置覆盖的项目写进基础配置
+                    err_log!(
+                        "Can't split the config from the app profile `{}`, the change is discarded.",
+                        name
+                    );
+                    return;
+                }
+            },
+        };
+        self.config.store(Arc::new(base));
+        self.update_merged();
 
-        self.update_time.store(get_time_stamp(), Ordering::Relaxed);
-        self.write();
+        self.dirty.store(true, Ordering::Release);
+        self.write_notify.notify_one();
     }
 
-    /*
-     * 读取配置数据。如果不存在配置文件，写入默认配置
+    //noinspection StructuralWrap
+    /**
+     * 根据前景窗口的应用程序切换配置，没有这个应用程序的配置时使用基础配置，如果配置改变了则返回true。
+     * `app` 应用程序的进程名称。
      * */
-    pub(crate) fn read(&self) -> ConfigRoot {
-        _read_config(&self.path).unwrap_or_else(|_| {
-            err_log!("The config file is invalid, this will be regenerated.");
-            _write_config(&self.path, &ConfigRoot::default()).unwrap_or_else(|_| {
-                err_log!("Can't write the default config file.");
-            });
-            info!("Using default config.");
-            Default::default()
-        })
-    }
-
-    // 延时写出配置数据。
-    fn write(&self) {
-        let path = self.path.clone();
-        let config = self.config.clone();
-        let update_time = self.update_time.clone();
-        let write_finished = self.write_finished.clone();
-
-        // 写出操作再新的线程进行
-        spawn(move || {
-            // 延时写入没有完成前，防止重复调用
-            if !write_finished.load(Ordering::Acquire) {
-                return;
+    pub(crate) fn switch_profile(&self, app: Option<&str>) -> bool {
+        *self.app.lock() = app.map(str::to_string);
+        let name = app.and_then(|a| self.config.load().app_profiles_config.find(a));
+        {
+            let mut lock = self.profile.lock();
+            if *lock == name {
+                return false;
             }
-            write_finished.store(false, Ordering::SeqCst);
+            *lock = name;
+        }
+        self.update_merged();
+        true
+    }
 
-            // 延迟到当前配置更新时间的推后10秒钟
+    //noinspection StructuralWrap
+    /**
+     * 取出启动时读取配置文件发现的问题，例如超出范围的值和无法解析的配置项，每个问题只会取出一次。
+     * */
+    pub(crate) fn take_problems(&self) -> Vec<ConfigProblem> {
+        std::mem::take(&mut *self.problems.lock())
+    }
+
+    /// 获取前景窗口的应用程序的进程名称，在切换应用程序的配置时记录
+    pub(crate) fn get_app(&self) -> Option<String> {
+        self.app.lock().clone()
+    }
+
+    /// 获取当前应用程序配置的名称，使用基础配置时返回None
+    pub(crate) fn get_profile(&self) -> Option<String> {
+        self.profile.lock().clone()
+    }
+
+    //noinspection StructuralWrap
+    /**
+     * 订阅一部分配置的修改，无论是通过程序修改还是在外部编辑了配置文件，只要这部分配置改变了就会调用监听器。
+     * `listener` 监听器，参数是修改后的配置。
+     * */
+    pub(crate) fn subscribe<T: ConfigSection>(
+        &self,
+        listener: impl Fn(&T) + Send + Sync + 'static,
+    ) {
+        self.listeners
+            .lock()
+            .push(Arc::new(move |old: &ConfigRoot, new: &ConfigRoot| {
+                let section = T::get_section(new);
+                if T::get_section(old) != section {
+                    listener(section);
+                }
+            }));
+    }
+
+    //noinspection StructuralWrap
+    /**
+     * 启动配置管理器的后台任务，重复调用不会启动多个任务。
+     * 一个任务监视配置文件，文件被外部修改后重新读取，新的配置无法解析时继续使用当前的配置；
+     * 另一个任务在配置修改一段时间后写入配置文件，这段时间内的多次修改只写入一次。
+     * `runtime` 运行后台任务的运行时。
+     * */
+    pub(crate) fn start(self: &Arc<Self>, runtime: &Runtime) {
+        if self.started.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let this = Arc::downgrade(self);
+        runtime.spawn(async move {
+            let mut interval = interval(WATCH_INTERVAL);
+            loop {
+                interval.tick().await;
+                let Some(this) = this.upgrade() else {
+                    break;
+                };
+                this.reload_if_changed();
+            }
+        });
+
+        let this = Arc::downgrade(self);
+        let notify = self.write_notify.clone();
+        runtime.spawn(async move {
             loop {
-                if get_time_stamp() - update_time.load(Ordering::Acquire) > 10 {
+                notify.notified().await;
+                // 每次新的修改都重新开始计时
+                while timeout(WRITE_DELAY, notify.notified()).await.is_ok() {}
+                let Some(this) = this.upgrade() else {
                     break;
+                };
+                spawn_blocking(move || this.flush()).await.unwrap_or(());
+            }
+        });
+    }
+
+    //noinspection StructuralWrap
+    /**
+     * 如果有还没有写入的修改，立即写入配置文件。退出程序时会调用这个方法，保证最后的修改不会丢失。
+     * */
+    pub(crate) fn flush(&self) {
+        let _lock = self.write_lock.lock();
+        if self.dirty.swap(false, Ordering::AcqRel) {
+            self.write_now();
+        }
+    }
+
+    // 如果配置文件的修改时间变了，重新读取配置文件
+    fn reload_if_changed(&self) {
+        // 还有没写入的修改时不重新读取，否则这些修改会被文件中的配置覆盖，写入以后会记录新的修改时间
+        if self.dirty.load(Ordering::Acquire) {
+            return;
+        }
+        let time = _get_file_time(&self.path);
+        {
+            let mut lock = self.file_time.lock();
+            if time.is_none() || *lock == time {
+                return;
+            }
+            *lock = time;
+        }
+
+        match _read_config(&self.path) {
+            Ok(file) => {
+                // 读取文件的过程中配置被修改了，留给下一次检查
+                if self.dirty.load(Ordering::Acquire) {
+                    *self.file_time.lock() = None;
+                    return;
                 }
-                sleep(Duration::from_secs(10));
+                info!("The config file is changed, reloading.");
+                self.config.store(Arc::new(self.load_file(file)));
+                self.update_merged();
             }
+            Err(e) => err_log!(
+                "Can't reload the config file, keep the current config. {}",
+                e
+            ),
+        }
+    }
 
-            // 开始执行配置写入
-            let cfg = config.load();
-            _write_config(&path, &cfg).unwrap_or_else(|_| err_log!("Can't write the config file."));
+    // 重新计算合并了应用程序配置的配置，并通知监听器
+    fn update_merged(&self) {
+        let base = self.config.load_full();
+        let profile = self.profile.lock().clone();
+        let merged = profile
+            .and_then(|name| apply_profile(&base, &name))
+            .unwrap_or_else(|| base.deref().clone());
+        let old = self.merged.swap(Arc::new(merged));
 
-            write_finished.store(true, Ordering::SeqCst);
+        // 复制一份监听器再调用，监听器中可以再次订阅或者修改配置
+        let listeners = self.listeners.lock().clone();
+        let new = self.merged.load_full();
+        for listener in listeners {
+            listener(&old, &new);
+        }
+    }
+
+    /*
+     * 读取配置数据。旧版本的配置文件会先迁移到当前版本再写回；如果不存在配置文件，写入默认配置；
+     * 如果配置文件无法解析，先备份原来的文件，再尝试从轮换的备份中恢复，都不能使用时写入默认配置，备份失败时不会覆盖原来的文件
+     * */
+    pub(crate) fn read(&self) -> ConfigRoot {
+        let err = match _read_config(&self.path) {
+            Ok(file) => {
+                let migrated = file.migrated;
+                let config = self.load_file(file);
+                if migrated {
+                    _write_config(&self.path, &config, &self.unknown.lock()).unwrap_or_else(|_| {
+                        err_log!("Can't write the migrated config file.");
+                    });
+                }
+                return config;
+            }
+            Err(e) => e,
+        };
+
+        if self.path.exists() {
+            err_log!(
+                "The config file is invalid, this will be regenerated. {}",
+                err
+            );
+            match _backup_config(&self.path) {
+                Ok(backup) => info!("The invalid config file is saved to {}.", backup.display()),
+                Err(e) => {
+                    err_log!(
+                        "Can't back up the invalid config file, using default config. {}",
+                        e
+                    );
+                    return Default::default();
+                }
+            }
+            // 无效的文件已经备份，删除后不会进入轮换的备份中
+            remove_file(&self.path).unwrap_or(());
+
+            // 尝试从最近的备份中恢复
+            for i in 1..=BACKUP_COUNT {
+                let backup = _get_backup_path(&self.path, i);
+                if let Ok(file) = _read_config(&backup) {
+                    info!("The config is restored from {}.", backup.display());
+                    let config = self.load_file(file);
+                    _write_config(&self.path, &config, &self.unknown.lock()).unwrap_or_else(|_| {
+                        err_log!("Can't write the restored config file.");
+                    });
+                    return config;
+                }
+            }
+        }
+        _write_config(&self.path, &ConfigRoot::default(), &Table::new()).unwrap_or_else(|_| {
+            err_log!("Can't write the default config file.");
         });
+        info!("Using default config.");
+        Default::default()
+    }
+
+    // 保存读取的配置文件中不认识的项目和发现的问题，返回读取的配置
+    fn load_file(&self, file: ConfigFile) -> ConfigRoot {
+        for problem in file.problems.iter() {
+            warn!("The config item is invalid. {}", problem);
+        }
+        *self.unknown.lock() = file.unknown;
+        *self.problems.lock() = file.problems;
+        file.config
     }
 
     /// 直接保存配置，不延时
     pub(crate) fn save_config(&self) {
-        let path = self.path.clone();
-        let config = self.config.clone();
+        let _lock = self.write_lock.lock();
+        self.dirty.store(false, Ordering::Release);
+        self.write_now();
+    }
 
-        let _job = spawn(move || {
-            let cfg = config.load();
-            _write_config(&path, &cfg).unwrap_or_else(|_| err_log!("写入配置文件失败"));
-        })
-        .join();
+    // 写出配置数据，并记录配置文件的修改时间，自己写入的修改不需要重新读取
+    fn write_now(&self) {
+        _write_config(&self.path, &self.config.load(), &self.unknown.lock())
+            .unwrap_or_else(|e| err_log!("Can't write the config file. {}", e));
+        *self.file_time.lock() = _get_file_time(&self.path);
     }
 }
 
-// 写出配置到指定文件
-fn _write_config(path: &PathBuf, config: &ConfigRoot) -> Result<(), Box<dyn std::error::Error>> {
-    let cfg = toml::to_string(config)?;
-    let mut file = File::create(&path)?;
-    file.write_all(&cfg.into_bytes())?;
+// 写出配置到指定文件。先写入临时文件并同步到磁盘，轮换备份后再用临时文件替换配置文件，
+// 这样写入过程中崩溃或断电也不会留下空的或者不完整的配置文件，程序不认识的项目会保留下来
+fn _write_config(
+    path: &PathBuf,
+    config: &ConfigRoot,
+    unknown: &Table,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut doc = Table::try_from(config)?;
+    merge_unknown(&mut doc, unknown);
+    let cfg = toml::to_string(&doc)?;
+    if read_to_string(path).is_ok_and(|s| s == cfg) {
+        // 内容没有改变，不需要写入，也不需要轮换备份
+        return Ok(());
+    }
+
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let temp = path.with_file_name(name);
+    let mut file = File::create(&temp)?;
+    file.write_all(cfg.as_bytes())?;
+    file.sync_all()?;
+    drop(file);
+
+    if path.exists() {
+        _rotate_backups(path)
+            .unwrap_or_else(|e| err_log!("Can't rotate the config backups. {}", e));
+    }
+    rename(&temp, path)?;
 
     Ok(())
 }
 
-// 读取配置文件
-fn _read_config(path: &PathBuf) -> Result<ConfigRoot, Box<dyn std::error::Error>> {
+// 轮换配置文件的备份，最旧的备份被删除，当前的配置文件成为最新的备份
+fn _rotate_backups(path: &PathBuf) -> std::io::Result<()> {
+    for i in (1..BACKUP_COUNT).rev() {
+        let from = _get_backup_path(path, i);
+        if from.exists() {
+            rename(&from, _get_backup_path(path, i + 1))?;
+        }
+    }
+    copy(path, _get_backup_path(path, 1))?;
+
+    Ok(())
+}
+
+// 获取第index个轮换备份的路径，例如config.toml.1
+fn _get_backup_path(path: &PathBuf, index: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}", index));
+    path.with_file_name(name)
+}
+
+// 读取的配置文件
+struct ConfigFile {
+    config: ConfigRoot,
+    // 是否进行了迁移
+    migrated: bool,
+    // 程序不认识的项目
+    unknown: Table,
+    // 发现的问题
+    problems: Vec<ConfigProblem>,
+}
+
+// 读取配置文件，先把旧版本的配置迁移到当前版本，再宽松地解析并检查配置项的值。
+// 只有文件不存在或者不是有效的TOML文档时才返回错误，无法使用的配置项会被修正并记录在问题中
+fn _read_config(path: &PathBuf) -> Result<ConfigFile, Box<dyn std::error::Error>> {
     let mut data = read_to_string(path.clone())?;
-    let cfg = toml::from_str::<ConfigRoot>(data.as_mut_str())?;
+    let mut doc = toml::from_str::<Table>(data.as_mut_str())?;
+    let migrated = migrate(&mut doc);
+    let mut problems = vec![];
+    let (mut config, unknown) = parse_lenient::<ConfigRoot>(&doc, &mut problems);
+    config.validate("", &mut problems);
+
+    Ok(ConfigFile {
+        config,
+        migrated,
+        unknown,
+        problems,
+    })
+}
+
+// 备份配置文件，备份的文件名后面加上时间戳，例如config.toml.20240101120000.bak
+fn _backup_config(path: &PathBuf) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.bak", Local::now().format("%Y%m%d%H%M%S")));
+    let backup = path.with_file_name(name);
+    copy(path, &backup)?;
+
+    Ok(backup)
+}
 
-    Ok(cfg)
+// 获取配置文件的修改时间，文件不存在时返回None
+fn _get_file_time(path: &PathBuf) -> Option<SystemTime> {
+    metadata(path).and_then(|m| m.modified()).ok()
 }
 
-// 计算时间戳， 单位：秒
-fn get_time_stamp() -> u64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .expect("Time went backwards")
-        .as_secs()
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reload_if_changed() {
+        let path = std::env::temp_dir().join(format!("rigela-config-{}.toml", std::process::id()));
+        let mut config = ConfigRoot::default();
+        config.tts_config.speed = 60;
+        _write_config(&path, &config, &Table::new()).unwrap();
+
+        let manager = ConfigManager::new(path.clone());
+        manager.reload_if_changed();
+        assert_eq!(manager.get_config().tts_config.speed, 60);
+
+        // 在外部修改了配置文件，修改时间的精度可能不够，所以清除记录的时间
+        config.tts_config.speed = 80;
+        _write_config(&path, &config, &Table::new()).unwrap();
+        *manager.file_time.lock() = None;
+        manager.reload_if_changed();
+        assert_eq!(manager.get_config().tts_config.speed, 80);
+        assert_eq!(manager.config.load().tts_config.speed, 80);
+
+        remove_file(&path).unwrap();
+        remove_file(_get_backup_path(&path, 1)).unwrap_or(());
+    }
+
+    #[test]
+    fn test_reload_while_dirty() {
+        let path = std::env::temp_dir().join(format!("rigela-dirty-{}.toml", std::process::id()));
+        let mut config = ConfigRoot::default();
+        config.tts_config.speed = 60;
+        _write_config(&path, &config, &Table::new()).unwrap();
+
+        let manager = ConfigManager::new(path.clone());
+        manager.reload_if_changed();
+        let mut changed = manager.get_config();
+        changed.tts_config.speed = 70;
+        manager.set_config(&changed);
+
+        // 没写入的修改不会被外部修改的文件覆盖
+        config.tts_config.speed = 80;
+        _write_config(&path, &config, &Table::new()).unwrap();
+        *manager.file_time.lock() = None;
+        manager.reload_if_changed();
+        assert_eq!(manager.get_config().tts_config.speed, 70);
+
+        // 写入以后文件中是程序修改的配置
+        manager.flush();
+        manager.reload_if_changed();
+        assert_eq!(manager.get_config().tts_config.speed, 70);
+        assert_eq!(_read_config(&path).unwrap().config.tts_config.speed, 70);
+
+        remove_file(&path).unwrap();
+        for i in 1..=BACKUP_COUNT {
+            remove_file(_get_backup_path(&path, i)).unwrap_or(());
+        }
+    }
+
+    #[test]
+    fn test_set_config_without_profile() {
+        let path = std::env::temp_dir().join(format!("rigela-profile-{}.toml", std::process::id()));
+        let manager = ConfigManager::new(path.clone());
+        let speed = manager.get_config().tts_config.speed;
+
+        // 应用程序配置不存在时无法拆分，基础配置保持不变
+        *manager.profile.lock() = Some("missing".to_string());
+        let mut config = manager.get_config();
+        config.tts_config.speed = speed + 10;
+        manager.set_config(&config);
+        assert_eq!(manager.config.load().tts_config.speed, speed);
+        assert!(!manager.dirty.load(Ordering::Acquire));
+    }
 }
@@ -11,8 +11,12 @@
  * See the License for the specific language governing permissions and limitations under the License.
  */
 
+pub(crate) mod app_profile;
 pub(crate) mod general;
 pub(crate) mod hotkeys;
 pub(crate) mod mouse;
 pub(crate) mod navigation;
+pub(crate) mod sound;
+pub(crate) mod speech_dict;
 pub(crate) mod tts;
+pub(crate) mod voice_profile;
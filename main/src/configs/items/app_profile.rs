@@ -0,0 +1,38 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use toml::Table;
+
+/// 应用程序配置项，前景窗口切换到某个应用程序时，使用他的配置覆盖基础配置
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+#[serde(default)]
+pub(crate) struct AppProfilesConfig {
+    /// 每个应用程序的配置（进程名称 -> 配置），进程名称不区分大小写，例如"code.exe"。
+    /// 配置的结构和整个配置文件相同，但只需要写出需要覆盖的项目，例如tts_config中的speed。
+    pub(crate) profiles: HashMap<String, Table>,
+}
+
+impl AppProfilesConfig {
+    /**
+     * 查找应用程序的配置名称，进程名称不区分大小写。
+     * `app` 应用程序的进程名称。
+     * */
+    pub(crate) fn find(&self, app: &str) -> Option<String> {
+        self.profiles
+            .keys()
+            .find(|k| k.eq_ignore_ascii_case(app))
+            .cloned()
+    }
+}
@@ -16,11 +16,18 @@ use std::fmt::Display;
 use win_wrap::common::get_user_default_locale_name;
 
 /// 常规配置项
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
 pub(crate) struct GeneralConfig {
     pub(crate) run_on_startup: bool,
     pub(crate) auto_check_update: bool,
     pub(crate) lang: Lang,
+    /// 是否把朗读过的语音保存到日志文件夹中的记录文件
+    pub(crate) save_speech_transcript: bool,
+    /// 语音和音效的输出方式
+    pub(crate) audio_output: AudioOutput,
+    /// 连续朗读结束或被打断后，是否把插入点移动到最后朗读的位置
+    pub(crate) say_all_move_caret: bool,
 }
 
 impl Default for GeneralConfig {
@@ -29,10 +36,25 @@ impl Default for GeneralConfig {
             run_on_startup: false,
             auto_check_update: true,
             lang: Lang::FollowSystem,
+            save_speech_transcript: false,
+            audio_output: AudioOutput::default(),
+            say_all_move_caret: false,
         }
     }
 }
 
+/// 音频的输出方式
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub(crate) enum AudioOutput {
+    /// 通过声卡播放
+    #[default]
+    Device,
+    /// 把每个语音引擎的输出写入WAV文件（文件名后面会加上引擎的名称），用于调试和测试，音效不会发声
+    WavFile(String),
+    /// 不输出任何声音
+    Null,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, PartialOrd, PartialEq)]
 pub(crate) enum Lang {
     FollowSystem,
@@ -13,13 +13,32 @@
 
 use crate::commander::keyboard::combo_keys::ComboKey;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
 pub(crate) struct HotKeysConfig {
+    /// 自定义的热键（能力ID -> 热键），没有自定义热键的能力使用默认热键。
     pub(crate) talent_keys: HashMap<String, ComboKey>,
 }
 
+/// 键位文件，保存全部能力的热键，可以导出后分享给其他人使用
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub(crate) struct KeymapFile {
+    /// 能力ID -> 热键的字符串，例如"RigelA+Ctrl+Up"，导入时逐项解析，无法解析的项目会被跳过。
+    pub(crate) talent_keys: BTreeMap<String, String>,
+}
+
+/// 合并键位的结果
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct KeymapMerge {
+    /// 改变了热键的能力数量
+    pub(crate) count: usize,
+    /// 热键和其他能力冲突而没有合并的项目（能力ID，热键），按照能力ID排序
+    pub(crate) conflicts: Vec<(String, ComboKey)>,
+}
+
 impl Default for HotKeysConfig {
     fn default() -> Self {
         Self {
@@ -27,3 +46,164 @@ impl Default for HotKeysConfig {
         }
     }
 }
+
+impl HotKeysConfig {
+    /**
+     * 获取完整的键位（能力ID -> 热键），自定义热键覆盖默认热键。
+     * `defaults` 能力的默认热键。
+     * */
+    pub(crate) fn get_keymap(
+        &self,
+        defaults: &HashMap<String, ComboKey>,
+    ) -> HashMap<String, ComboKey> {
+        let mut keymap = defaults.clone();
+        keymap.extend(self.talent_keys.iter().map(|(k, v)| (k.clone(), *v)));
+        keymap
+    }
+
+    //noinspection StructuralWrap
+    /**
+     * 把键位合并到自定义热键中，返回改变了热键的能力数量和因为冲突没有合并的项目。
+     * 键位中没有的能力保持不变，和默认热键相同的项目会从自定义热键中移除。
+     * 同一个热键只能触发一个能力，合并后会和其他能力使用同一个热键的项目不会被合并，这些能力保持原来的热键。
+     * `keymap` 需要合并的键位（能力ID -> 热键）。
+     * `defaults` 能力的默认热键。
+     * */
+    pub(crate) fn merge_keymap(
+        &mut self,
+        mut keymap: HashMap<String, ComboKey>,
+        defaults: &HashMap<String, ComboKey>,
+    ) -> KeymapMerge {
+        let current = self.get_keymap(defaults);
+        keymap.retain(|id, key| current.get(id) != Some(key));
+
+        // 去掉冲突的项目以后，这些能力恢复原来的热键，可能又和其他的项目冲突，所以重复检查直到没有冲突
+        let mut conflicts = vec![];
+        loop {
+            let mut merged = current.clone();
+            merged.extend(keymap.iter().map(|(id, key)| (id.clone(), *key)));
+            let mut used = HashMap::new();
+            for key in merged.values() {
+                *used.entry(*key).or_insert(0) += 1;
+            }
+
+            let rejected = keymap
+                .iter()
+                .filter(|(_, key)| used[*key] > 1)
+                .map(|(id, _)| id.clone())
+                .collect::<Vec<_>>();
+            if rejected.is_empty() {
+                break;
+            }
+            for id in rejected {
+                if let Some(key) = keymap.remove(&id) {
+                    conflicts.push((id, key));
+                }
+            }
+        }
+        conflicts.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let count = keymap.len();
+        for (id, key) in keymap {
+            if defaults.get(&id) == Some(&key) {
+                self.talent_keys.remove(&id);
+            } else {
+                self.talent_keys.insert(id, key);
+            }
+        }
+
+        KeymapMerge { count, conflicts }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(list: &[(&str, &str)]) -> HashMap<String, ComboKey> {
+        list.iter()
+            .map(|(id, key)| (id.to_string(), key.parse().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_keymap() {
+        let defaults = keys(&[("exit", "RigelA+Escape"), ("read_mouse", "RigelA+M")]);
+        let mut config = HotKeysConfig {
+            talent_keys: keys(&[("exit", "RigelA+F12 double"), ("popup_menu", "RigelA+R")]),
+        };
+
+        // 恢复成默认热键的项目被移除，新的热键被加入，相同的热键不计数
+        let keymap = keys(&[
+            ("exit", "RigelA+Escape"),
+            ("read_mouse", "RigelA+Ctrl+M"),
+            ("popup_menu", "RigelA+R"),
+        ]);
+        assert_eq!(config.merge_keymap(keymap, &defaults).count, 2);
+        assert_eq!(
+            config.talent_keys,
+            keys(&[("read_mouse", "RigelA+Ctrl+M"), ("popup_menu", "RigelA+R")])
+        );
+
+        assert_eq!(
+            config.get_keymap(&defaults),
+            keys(&[
+                ("exit", "RigelA+Escape"),
+                ("read_mouse", "RigelA+Ctrl+M"),
+                ("popup_menu", "RigelA+R"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_merge_keymap_conflicts() {
+        let defaults = keys(&[
+            ("exit", "RigelA+Escape"),
+            ("read_mouse", "RigelA+M"),
+            ("popup_menu", "RigelA+R"),
+        ]);
+        let mut config = HotKeysConfig::default();
+
+        // 两个导入的项目互相冲突时都不会被合并，read_mouse保持原来的热键以后又和exit冲突
+        let keymap = keys(&[
+            ("exit", "RigelA+M"),
+            ("read_mouse", "RigelA+Ctrl+M"),
+            ("popup_menu", "RigelA+Ctrl+M"),
+        ]);
+        let result = config.merge_keymap(keymap, &defaults);
+        assert_eq!(result.count, 0);
+        let conflicts = result
+            .conflicts
+            .iter()
+            .map(|(id, key)| (id.as_str(), key.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            conflicts,
+            [
+                ("exit", "RigelA+M".to_string()),
+                ("popup_menu", "RigelA+Ctrl+M".to_string()),
+                ("read_mouse", "RigelA+Ctrl+M".to_string()),
+            ]
+        );
+        assert!(config.talent_keys.is_empty());
+
+        // 只和没有改变的能力冲突的项目不会被合并，其他的项目正常合并
+        let keymap = keys(&[("exit", "RigelA+R"), ("read_mouse", "RigelA+Ctrl+M")]);
+        let result = config.merge_keymap(keymap, &defaults);
+        assert_eq!(result.count, 1);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].0, "exit");
+        assert_eq!(config.talent_keys, keys(&[("read_mouse", "RigelA+Ctrl+M")]));
+        config.talent_keys.clear();
+
+        // 交换两个能力的热键不算冲突
+        let keymap = keys(&[("exit", "RigelA+M"), ("read_mouse", "RigelA+Escape")]);
+        let result = config.merge_keymap(keymap, &defaults);
+        assert_eq!(result.count, 2);
+        assert!(result.conflicts.is_empty());
+        assert_eq!(
+            config.talent_keys,
+            keys(&[("exit", "RigelA+M"), ("read_mouse", "RigelA+Escape")])
+        );
+    }
+}
@@ -14,7 +14,8 @@
 use serde::{Deserialize, Serialize};
 
 /// 鼠标配置项
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
 pub(crate) struct MouseConfig {
     /// 是否朗读鼠标元素
     pub(crate) is_read: bool,
@@ -16,7 +16,7 @@ use serde::{Deserialize, Serialize};
 /**
  * 导航模式。
  * */
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub(crate) enum NavigationMode {
     /// 线性模式
     Linear,
@@ -33,7 +33,8 @@ impl Default for NavigationMode {
 }
 
 /// 导航配置项
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
 pub(crate) struct NavigationConfig {
     /// 导航模式
     pub(crate) mode: NavigationMode,
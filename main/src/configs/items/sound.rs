@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 需要播放音效的事件
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub(crate) enum SoundEvent {
+    /// 到达边界，例如导航到第一个或最后一个元素
+    Boundary,
+    /// 编辑框中光标到达文字的边缘
+    Edge,
+    /// 打开对话框
+    Dialog,
+    /// 操作出错
+    Error,
+    /// 读屏启动
+    Launch,
+    /// 读屏退出
+    Exit,
+    /// 提示信息，例如弹出提示或输入法候选
+    Tip,
+    /// 进度条变化
+    Progress,
+    /// 朗读大写字母
+    Capital,
+}
+
+impl SoundEvent {
+    /// 所有的事件
+    pub(crate) const ALL: [SoundEvent; 9] = [
+        Self::Boundary,
+        Self::Edge,
+        Self::Dialog,
+        Self::Error,
+        Self::Launch,
+        Self::Exit,
+        Self::Tip,
+        Self::Progress,
+        Self::Capital,
+    ];
+
+    /// 内置音效的资源名称
+    pub(crate) fn default_file(&self) -> &'static str {
+        match self {
+            Self::Boundary => "boundary.wav",
+            Self::Edge => "edge.wav",
+            Self::Dialog => "dialog.wav",
+            Self::Error => "error.wav",
+            Self::Launch => "launch.wav",
+            Self::Exit => "exit.wav",
+            Self::Tip => "tip.wav",
+            Self::Progress => "progress.wav",
+            // 默认和提示信息使用同一个音效
+            Self::Capital => "tip.wav",
+        }
+    }
+}
+
+/// 一个事件的音效设置
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub(crate) struct SoundItem {
+    /// 音效的文件名称
+    pub(crate) file: String,
+    /// 音量，取值范围是0到100
+    pub(crate) volume: i32,
+    /// 是否播放，关闭后这个事件不再播放音效
+    pub(crate) enabled: bool,
+    /// 声道平衡，取值范围是-100（左声道）到100（右声道），0是居中
+    #[serde(default)]
+    pub(crate) pan: i32,
+}
+
+impl From<SoundEvent> for SoundItem {
+    fn from(event: SoundEvent) -> Self {
+        Self {
+            file: event.default_file().to_string(),
+            volume: 100,
+            enabled: true,
+            pan: 0,
+        }
+    }
+}
+
+/// 音效方案的配置项
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub(crate) struct SoundConfig {
+    /// 音效包的名称，对应程序目录下sounds文件夹中的同名文件夹，音效包中没有的文件使用内置的音效
+    pub(crate) scheme: Option<String>,
+    /// 每个事件的音效，没有设置的事件使用内置的音效
+    pub(crate) items: HashMap<SoundEvent, SoundItem>,
+}
+
+impl SoundConfig {
+    /// 获取事件的音效设置
+    pub(crate) fn get_item(&self, event: SoundEvent) -> SoundItem {
+        self.items
+            .get(&event)
+            .cloned()
+            .unwrap_or_else(|| event.into())
+    }
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        Self {
+            scheme: None,
+            items: SoundEvent::ALL.into_iter().map(|e| (e, e.into())).collect(),
+        }
+    }
+}
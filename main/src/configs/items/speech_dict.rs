@@ -0,0 +1,46 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use serde::{Deserialize, Serialize};
+
+/// 词典条目的匹配方式
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub(crate) enum DictEntryKind {
+    /// 按原文匹配
+    Literal,
+    /// 按整个单词匹配
+    WholeWord,
+    /// 按正则表达式匹配，替换文字中可以使用$1这样的分组引用
+    Regex,
+}
+
+/// 语音词典的条目
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub(crate) struct DictEntry {
+    /// 要查找的文字或正则表达式
+    pub(crate) pattern: String,
+    /// 替换成的文字
+    pub(crate) replacement: String,
+    pub(crate) kind: DictEntryKind,
+    /// 是否区分大小写
+    pub(crate) case_sensitive: bool,
+    /// 只在指定的应用程序中生效（进程名称，例如notepad.exe），没有设置则全局生效
+    pub(crate) app: Option<String>,
+}
+
+/// 语音词典配置项
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq)]
+pub(crate) struct SpeechDictConfig {
+    /// 词典条目，按照列表中的顺序依次应用
+    pub(crate) entries: Vec<DictEntry>,
+}
@@ -12,24 +12,197 @@
  */
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// 属性条目
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 pub(crate) enum TtsPropertyItem {
     Speed,
     Pitch,
     Volume,
     Voice,
+    /// 语音引擎特有的参数（参数名称）
+    Extra(String),
+}
+
+/// 标点符号的朗读级别，级别越高朗读的符号越多
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub(crate) enum SymbolLevel {
+    /// 不朗读任何符号
+    Off,
+    /// 只朗读少量的特殊符号
+    #[default]
+    Low,
+    /// 朗读大部分符号
+    Most,
+    /// 朗读所有的符号
+    All,
+    /// 逐字朗读单个字符时才朗读的符号（例如空格），朗读句子时和All相同，不参与级别的切换
+    Character,
+}
+
+/// 数字的朗读方式
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub(crate) enum NumberMode {
+    /// 交给语音引擎处理
+    #[default]
+    Engine,
+    /// 逐位朗读数字，例如“一二三四”
+    Digits,
+    /// 按照完整的数值朗读，例如“一千二百三十四”
+    Full,
+}
+
+/// 大写字母的提示方式，可以同时使用多种方式
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub(crate) struct CapitalConfig {
+    /// 朗读大写字母时提高音调的幅度，0表示不提高
+    pub(crate) pitch_change: i32,
+    /// 是否在大写字母前面朗读“大写”
+    pub(crate) say_prefix: bool,
+    /// 是否播放大写字母的音效
+    pub(crate) play_sound: bool,
+    /// 逐字朗读单词时，是否也提示其中的每一个大写字母
+    pub(crate) in_spelled_words: bool,
+}
+
+impl Default for CapitalConfig {
+    fn default() -> Self {
+        Self {
+            pitch_change: 20,
+            say_prefix: false,
+            play_sound: false,
+            in_spelled_words: false,
+        }
+    }
+}
+
+/// 文字的书写系统，用于自动切换朗读的语言
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub(crate) enum Script {
+    /// 汉字（包括中文标点）
+    Han,
+    /// 拉丁字母（例如英文）
+    Latin,
+}
+
+/// 外部命令读取文字的方式
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub(crate) enum CommandTextInput {
+    /// 通过标准输入写入文字（UTF-8编码）
+    #[default]
+    Stdin,
+    /// 通过参数中的{text}传入文字
+    Argument,
+}
+
+/// 外部命令输出的音频格式
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Default)]
+pub(crate) enum CommandOutputFormat {
+    /// 16位的原始PCM数据
+    #[default]
+    Pcm,
+    /// 带文件头的WAV数据
+    Wav,
+}
+
+/// 外部命令的参数模板
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub(crate) struct CommandArgTemplate {
+    /// 参数列表，其中的{value}会被替换成换算后的值，例如["-s", "{value}"]
+    pub(crate) args: Vec<String>,
+    /// 读屏的值0和100分别对应的命令参数值，中间的值按照线性换算
+    pub(crate) range: (i32, i32),
+}
+
+/// 外部命令语音引擎的配置项
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
+pub(crate) struct CommandEngineConfig {
+    /// 引擎名称，不能和其他引擎重复
+    pub(crate) name: String,
+    /// 合成器的可执行文件路径
+    pub(crate) program: String,
+    /// 固定的参数列表，可以使用{text}、{rate}、{pitch}、{volume}和{voice}占位符
+    pub(crate) args: Vec<String>,
+    pub(crate) text_input: CommandTextInput,
+    pub(crate) output_format: CommandOutputFormat,
+    pub(crate) sample_rate: u32,
+    pub(crate) channels: u32,
+    /// 语速、音调和音量的参数模板，没有设置的属性不会传给命令
+    pub(crate) rate: Option<CommandArgTemplate>,
+    pub(crate) pitch: Option<CommandArgTemplate>,
+    pub(crate) volume: Option<CommandArgTemplate>,
+    /// 语音的参数模板，{value}会被替换成语音ID
+    pub(crate) voice_args: Vec<String>,
+    /// 可以选择的语音（语音ID，语音名称）
+    pub(crate) voices: Vec<(String, String)>,
+    /// 合成器可以直接朗读SSML时设置，朗读SSML时额外传给命令的参数，例如espeak-ng的["-m"]
+    pub(crate) ssml_args: Option<Vec<String>>,
+}
+
+impl Default for CommandEngineConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            program: String::new(),
+            args: vec![],
+            text_input: CommandTextInput::default(),
+            output_format: CommandOutputFormat::default(),
+            sample_rate: 16000,
+            channels: 1,
+            rate: None,
+            pitch: None,
+            volume: None,
+            voice_args: vec![],
+            voices: vec![],
+            ssml_args: None,
+        }
+    }
+}
+
+/// 语音缓存的配置项，合成过的短文字的语音会保存在内存中，再次朗读时不需要重新合成
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+pub(crate) struct PcmCacheConfig {
+    pub(crate) enabled: bool,
+    /// 只缓存不超过这个字符数量的文字
+    pub(crate) max_chars: usize,
+    /// 缓存最多占用的内存，单位是KB
+    pub(crate) max_memory: usize,
+}
+
+impl Default for PcmCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_chars: 8,
+            max_memory: 16384,
+        }
+    }
 }
 
 /// 语音TTS的配置项
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(default)]
 pub(crate) struct TtsConfig {
     pub(crate) speed: i32,
     pub(crate) volume: i32,
     pub(crate) pitch: i32,
     pub(crate) voice: (String, String),
     pub(crate) item: TtsPropertyItem,
+    pub(crate) symbol_level: SymbolLevel,
+    pub(crate) number_mode: NumberMode,
+    pub(crate) capital: CapitalConfig,
+    /// 每种书写系统使用的语音（引擎名称，语音ID），没有设置的书写系统使用voice中的语音
+    pub(crate) language_voices: HashMap<Script, (String, String)>,
+    /// 通过外部命令合成语音的引擎
+    pub(crate) command_engines: Vec<CommandEngineConfig>,
+    /// 每个引擎特有参数的值（引擎名称 -> 参数名称 -> 值），没有保存的参数使用引擎的默认值
+    pub(crate) engine_params: HashMap<String, HashMap<String, i32>>,
+    pub(crate) pcm_cache: PcmCacheConfig,
+    /// 长文字分段朗读时每一段最多的字符数，超过这个长度的文字在句子和分句的标点处切分，0表示不分段
+    pub(crate) segment_length: usize,
 }
 
 impl Default for TtsConfig {
@@ -41,6 +214,14 @@ impl Default for TtsConfig {
             pitch: 50,
             voice: ("Sapi5".to_string(), "HKEY_LOCAL_MACHINE\\SOFTWARE\\Microsoft\\Speech_OneCore\\Voices\\Tokens\\MSTTS_V110_zhCN_HuihuiM".to_string()),
             item: TtsPropertyItem::Speed,
+            symbol_level: SymbolLevel::default(),
+            number_mode: NumberMode::default(),
+            capital: CapitalConfig::default(),
+            language_voices: HashMap::new(),
+            command_engines: vec![],
+            engine_params: HashMap::new(),
+            pcm_cache: PcmCacheConfig::default(),
+            segment_length: 60,
         }
     }
 }
@@ -0,0 +1,32 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::configs::items::tts::TtsConfig;
+use serde::{Deserialize, Serialize};
+
+/// 语音方案，保存一套完整的语音设置
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub(crate) struct VoiceProfile {
+    /// 方案名称，切换时会朗读
+    pub(crate) name: String,
+    pub(crate) tts_config: TtsConfig,
+}
+
+/// 语音方案配置项
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub(crate) struct VoiceProfilesConfig {
+    /// 所有的方案，按照列表中的顺序切换
+    pub(crate) profiles: Vec<VoiceProfile>,
+    /// 当前使用的方案名称
+    pub(crate) current: Option<String>,
+}
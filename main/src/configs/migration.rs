@@ -0,0 +1,265 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use log::{info, warn};
+use toml::{Table, Value};
+
+/// 当前配置文件的版本，修改配置的结构时需要增加版本号，并在MIGRATIONS中加入对应的迁移函数
+pub(crate) const CONFIG_VERSION: u32 = 2;
+
+/// 版本号在配置文件中的键
+const VERSION_KEY: &str = "version";
+
+/// 升级配置文档的函数，第i个函数把版本i的文档升级到版本i+1
+type Migration = fn(&mut Table);
+
+/// 按照版本顺序排列的迁移函数
+const MIGRATIONS: [Migration; CONFIG_VERSION as usize] = [migrate_0_to_1, migrate_1_to_2];
+
+/**
+ * 获取配置文档的版本，加入版本号之前的配置文件是版本0。
+ * `doc` 配置文档。
+ * */
+fn get_version(doc: &Table) -> u32 {
+    doc.get(VERSION_KEY)
+        .and_then(Value::as_integer)
+        .map_or(0, |v| v.max(0) as u32)
+}
+
+//noinspection StructuralWrap
+/**
+ * 把配置文档依次升级到当前的版本，如果文档被修改了则返回true。
+ * 比当前版本更新的文档（例如使用新版本的程序保存过）不会被修改，读取时不认识的配置项会被忽略。
+ * `doc` 配置文档。
+ * */
+pub(crate) fn migrate(doc: &mut Table) -> bool {
+    migrate_with(doc, &MIGRATIONS)
+}
+
+fn migrate_with(doc: &mut Table, migrations: &[Migration]) -> bool {
+    let version = get_version(doc);
+    let target = migrations.len() as u32;
+    if version > target {
+        warn!(
+            "The config file version {version} is newer than {target}, some items may be ignored."
+        );
+        return false;
+    }
+    if version == target {
+        return false;
+    }
+
+    for (i, migration) in migrations.iter().enumerate().skip(version as usize) {
+        migration(doc);
+        info!("The config file is migrated to version {}.", i + 1);
+    }
+    doc.insert(VERSION_KEY.to_string(), Value::Integer(target as i64));
+
+    true
+}
+
+// 版本0是加入版本号之前的配置文件，结构和版本1相同，只需要写入版本号
+fn migrate_0_to_1(_doc: &mut Table) {}
+
+// 版本2把热键从结构体改为可读的字符串，例如{ main_key = "VkF12", modify_keys = "RIGELA", state = "DoublePress" }改为"RigelA+F12 double"
+fn migrate_1_to_2(doc: &mut Table) {
+    migrate_talent_keys(doc);
+
+    // 应用程序的配置也可以覆盖热键
+    if let Some(Value::Table(app_profiles)) = doc.get_mut("app_profiles_config") {
+        if let Some(Value::Table(profiles)) = app_profiles.get_mut("profiles") {
+            for (_, profile) in profiles.iter_mut() {
+                if let Value::Table(profile) = profile {
+                    migrate_talent_keys(profile);
+                }
+            }
+        }
+    }
+}
+
+fn migrate_talent_keys(doc: &mut Table) {
+    let Some(Value::Table(hotkeys)) = doc.get_mut("hotkeys_config") else {
+        return;
+    };
+    let Some(Value::Table(talent_keys)) = hotkeys.get_mut("talent_keys") else {
+        return;
+    };
+
+    for (id, key) in talent_keys.iter_mut() {
+        let Value::Table(table) = key else {
+            continue;
+        };
+        match get_combo_key_text(table) {
+            Some(text) => *key = Value::String(text),
+            None => warn!("Can't migrate the hotkey of `{id}`: {table}"),
+        }
+    }
+}
+
+// 修饰键的位、ModifierKeys中的标志名称和组合键字符串中的名称，和ModifierKeys的位标志保持一致
+const MODIFIERS: [(i64, &str, &str); 5] = [
+    (0b0000_0001, "RIGELA", "RigelA"),
+    (0b0000_0010, "CTRL", "Ctrl"),
+    (0b0000_0100, "ALT", "Alt"),
+    (0b0000_1000, "SHIFT", "Shift"),
+    (0b0001_0000, "WIN", "Win"),
+];
+
+// 把旧版本的组合键结构体转换为字符串
+fn get_combo_key_text(table: &Table) -> Option<String> {
+    let main_key = table.get("main_key")?.as_str()?;
+    let modify_keys = get_modify_keys(table.get("modify_keys"))?;
+    let state = match table.get("state").and_then(Value::as_str) {
+        Some("DoublePress") => " double",
+        Some("LongPress") => " long",
+        _ => "",
+    };
+
+    let mut text = String::new();
+    for (bit, _, name) in MODIFIERS {
+        if modify_keys & bit != 0 {
+            text.push_str(name);
+            text.push('+');
+        }
+    }
+    text.push_str(main_key.strip_prefix("Vk").unwrap_or(main_key));
+    text.push_str(state);
+
+    Some(text)
+}
+
+//noinspection StructuralWrap
+/*
+ * 获取旧版本的修饰键的位。bitflags把修饰键序列化为用“|”分隔的标志名称，例如"RIGELA | CTRL"，没有修饰键时是空字符串，不认识的位会写成十六进制数；
+ * 手动编辑过的文件中也可能是整数。
+ * */
+fn get_modify_keys(value: Option<&Value>) -> Option<i64> {
+    let text = match value {
+        None => return Some(0),
+        Some(Value::Integer(bits)) => return Some(*bits),
+        Some(Value::String(text)) => text,
+        Some(_) => return None,
+    };
+
+    text.split('|')
+        .map(str::trim)
+        .filter(|flag| !flag.is_empty())
+        .try_fold(0, |bits, flag| {
+            let bit = match flag.strip_prefix("0x") {
+                Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+                None => MODIFIERS.iter().find(|(_, f, _)| *f == flag)?.0,
+            };
+            Some(bits | bit)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commander::keyboard::{combo_keys::State, keys::Keys, modify_keys::ModifierKeys};
+    use serde::Serialize;
+
+    // 按照版本1的ComboKey结构体序列化组合键，得到和旧版本的程序写出的文件相同的内容
+    fn old_combo_key(main_key: Keys, modify_keys: ModifierKeys, state: State) -> String {
+        #[derive(Serialize)]
+        struct ComboKey {
+            main_key: Keys,
+            modify_keys: ModifierKeys,
+            state: State,
+        }
+
+        toml::to_string(&ComboKey {
+            main_key,
+            modify_keys,
+            state,
+        })
+        .unwrap()
+    }
+
+    // 测试用的迁移：把general_config.lang改名为language
+    fn rename_lang(doc: &mut Table) {
+        if let Some(Value::Table(general)) = doc.get_mut("general_config") {
+            if let Some(lang) = general.remove("lang") {
+                general.insert("language".to_string(), lang);
+            }
+        }
+    }
+
+    // 测试用的迁移：加入一个新的配置项
+    fn add_item(doc: &mut Table) {
+        doc.insert("new_item".to_string(), Value::Boolean(true));
+    }
+
+    #[test]
+    fn test_migrate() {
+        let migrations: [Migration; 2] = [rename_lang, add_item];
+        let mut doc = toml::from_str::<Table>("[general_config]\nlang = \"Zh\"\n").unwrap();
+        assert!(migrate_with(&mut doc, &migrations));
+        assert_eq!(get_version(&doc), 2);
+        assert_eq!(doc["general_config"]["language"].as_str(), Some("Zh"));
+        assert_eq!(doc["new_item"].as_bool(), Some(true));
+
+        // 已经是最新版本的文档不会再次迁移
+        assert!(!migrate_with(&mut doc, &migrations));
+
+        // 从中间的版本开始迁移
+        let mut doc =
+            toml::from_str::<Table>("version = 1\n[general_config]\nlang = \"Zh\"\n").unwrap();
+        assert!(migrate_with(&mut doc, &migrations));
+        assert_eq!(doc["general_config"]["lang"].as_str(), Some("Zh"));
+        assert!(doc.contains_key("new_item"));
+
+        // 更新版本的文档保持不变
+        let mut doc = toml::from_str::<Table>("version = 5\n").unwrap();
+        assert!(!migrate_with(&mut doc, &migrations));
+        assert_eq!(get_version(&doc), 5);
+    }
+
+    #[test]
+    fn test_migrate_1_to_2() {
+        let data = format!(
+            r#"
+version = 1
+[hotkeys_config.talent_keys]
+popup_menu = "RigelA+R"
+speak_time = {{ main_key = "VkF1", modify_keys = 8, state = "SinglePress" }}
+[hotkeys_config.talent_keys.stop_tts_output]
+{}
+[hotkeys_config.talent_keys.exit]
+{}
+[app_profiles_config.profiles."code.exe".hotkeys_config.talent_keys.read_mouse]
+{}
+"#,
+            old_combo_key(
+                Keys::VkUp,
+                ModifierKeys::RIGELA | ModifierKeys::CTRL,
+                State::SinglePress
+            ),
+            old_combo_key(Keys::VkF12, ModifierKeys::RIGELA, State::DoublePress),
+            old_combo_key(Keys::VkNumPad5, ModifierKeys::empty(), State::LongPress),
+        );
+        let mut doc = toml::from_str::<Table>(&data).unwrap();
+        assert!(migrate(&mut doc));
+        assert_eq!(get_version(&doc), 2);
+
+        let keys = &doc["hotkeys_config"]["talent_keys"];
+        assert_eq!(keys["stop_tts_output"].as_str(), Some("RigelA+Ctrl+Up"));
+        assert_eq!(keys["exit"].as_str(), Some("RigelA+F12 double"));
+        assert_eq!(keys["popup_menu"].as_str(), Some("RigelA+R"));
+        // 整数形式的修饰键也可以迁移
+        assert_eq!(keys["speak_time"].as_str(), Some("Shift+F1"));
+        let keys =
+            &doc["app_profiles_config"]["profiles"]["code.exe"]["hotkeys_config"]["talent_keys"];
+        assert_eq!(keys["read_mouse"].as_str(), Some("NumPad5 long"));
+    }
+}
@@ -15,11 +15,20 @@ use crate::{
     commander::keyboard::combo_keys::ComboKey,
     configs::items::{
         general::{GeneralConfig, Lang},
+        hotkeys::{KeymapFile, KeymapMerge},
         mouse::MouseConfig,
+        speech_dict::SpeechDictConfig,
     },
     context::{Context, ContextAccessor},
 };
-use std::{collections::HashMap, sync::Weak};
+use log::warn;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::{read_to_string, write},
+    path::Path,
+    sync::Weak,
+};
 
 // ------  鼠标配置  ------
 
@@ -58,6 +67,66 @@ pub(crate) fn save_hotkeys(context: Weak<Context>, hotkeys: HashMap<String, Comb
     context.get_config_manager().set_config(&config);
 }
 
+/**
+ * 导出键位文件，包含全部能力当前使用的热键，自定义热键覆盖默认热键。
+ * `path` 键位文件的路径（toml格式）。
+ * */
+pub(crate) fn export_keymap(context: Weak<Context>, path: &Path) -> Result<(), Box<dyn Error>> {
+    let defaults = context.get_talent_provider().get_default_combo_keys();
+    let keymap = context
+        .get_config_manager()
+        .get_config()
+        .hotkeys_config
+        .get_keymap(&defaults);
+    let file = KeymapFile {
+        talent_keys: keymap
+            .into_iter()
+            .map(|(id, key)| (id, key.to_string()))
+            .collect(),
+    };
+    write(path, toml::to_string(&file)?)?;
+
+    Ok(())
+}
+
+//noinspection StructuralWrap
+/**
+ * 从文件导入键位并和当前的热键合并，文件中没有的能力保持不变，和默认热键相同的能力恢复使用默认热键。
+ * 返回改变了热键的能力数量和因为热键冲突没有导入的项目，不认识的能力和无法解析的热键会被跳过。
+ * `path` 键位文件的路径（toml格式）。
+ * */
+pub(crate) fn import_keymap(
+    context: Weak<Context>,
+    path: &Path,
+) -> Result<KeymapMerge, Box<dyn Error>> {
+    let file: KeymapFile = toml::from_str(&read_to_string(path)?)?;
+
+    let talent_provider = context.get_talent_provider();
+    let mut keymap = HashMap::new();
+    for (id, text) in file.talent_keys {
+        if talent_provider.get_talent_by_id(&id).is_none() {
+            warn!("Unknown talent `{id}` in the keymap file, skipped.");
+            continue;
+        }
+        match text.parse::<ComboKey>() {
+            Ok(key) => {
+                keymap.insert(id, key);
+            }
+            Err(e) => warn!("Can't parse the hotkey of `{id}`. {e}"),
+        }
+    }
+
+    let defaults = talent_provider.get_default_combo_keys();
+    let mut config = context.get_config_manager().get_config();
+    let result = config.hotkeys_config.merge_keymap(keymap, &defaults);
+    for (id, key) in result.conflicts.iter() {
+        warn!("The hotkey `{key}` of `{id}` conflicts with other talents, skipped.");
+    }
+    context.get_config_manager().set_config(&config);
+
+    Ok(result)
+}
+
 // ------  常规配置  -------
 
 /// 获取是否开机自启
@@ -98,6 +167,25 @@ pub(crate) fn save_auto_check_update(context: Weak<Context>, auto_check_update:
     context.get_config_manager().set_config(&config);
 }
 
+/// 获取是否保存语音记录
+pub(crate) fn get_speech_transcript(context: Weak<Context>) -> bool {
+    context
+        .get_config_manager()
+        .get_config()
+        .general_config
+        .save_speech_transcript
+}
+
+/// 保存是否保存语音记录
+pub(crate) fn save_speech_transcript(context: Weak<Context>, save_speech_transcript: bool) {
+    let mut config = context.get_config_manager().get_config();
+    config.general_config = GeneralConfig {
+        save_speech_transcript,
+        ..config.general_config
+    };
+    context.get_config_manager().set_config(&config);
+}
+
 /// 获取当前语言
 pub(crate) fn get_lang(context: Weak<Context>) -> Lang {
     context
@@ -116,3 +204,43 @@ pub(crate) fn save_lang(context: Weak<Context>, lang: &Lang) {
     };
     context.get_config_manager().set_config(&config);
 }
+
+// ------  语音词典  -------
+
+/**
+ * 从文件导入语音词典，导入的条目追加到现有词典的末尾，已经存在的条目会被跳过。
+ * 返回新增的条目数量。
+ * `path` 词典文件的路径（toml格式）。
+ * */
+pub(crate) fn import_speech_dict(
+    context: Weak<Context>,
+    path: &Path,
+) -> Result<usize, Box<dyn Error>> {
+    let dict: SpeechDictConfig = toml::from_str(&read_to_string(path)?)?;
+
+    let mut config = context.get_config_manager().get_config();
+    let mut count = 0;
+    for entry in dict.entries {
+        if !config.speech_dict_config.entries.contains(&entry) {
+            config.speech_dict_config.entries.push(entry);
+            count += 1;
+        }
+    }
+    context.get_config_manager().set_config(&config);
+
+    Ok(count)
+}
+
+/**
+ * 导出语音词典到文件。
+ * `path` 词典文件的路径（toml格式）。
+ * */
+pub(crate) fn export_speech_dict(
+    context: Weak<Context>,
+    path: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let dict = context.get_config_manager().get_config().speech_dict_config;
+    write(path, toml::to_string(&dict)?)?;
+
+    Ok(())
+}
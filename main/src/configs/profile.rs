@@ -0,0 +1,167 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::configs::{validation::Validate, ConfigRoot};
+use log::{error, warn};
+use toml::{Table, Value};
+
+/// 应用程序配置中不能覆盖的项目
+const IGNORED_KEYS: [&str; 2] = ["version", "app_profiles_config"];
+
+/**
+ * 把应用程序的配置合并到基础配置上，返回合并后的配置。
+ * `base` 基础配置。
+ * `name` 应用程序配置的名称。
+ * */
+pub(crate) fn apply_profile(base: &ConfigRoot, name: &str) -> Option<ConfigRoot> {
+    let overlay = get_overlay(base, name)?;
+    let mut table = Table::try_from(base).ok()?;
+    merge_overlay(&mut table, &overlay);
+    let mut config: ConfigRoot = Value::Table(table)
+        .try_into()
+        .inspect_err(|e| error!("The app profile `{}` is invalid. {}", name, e))
+        .ok()?;
+
+    // 应用程序配置中的值和配置文件一样需要检查，无效的值会被修正
+    let mut problems = vec![];
+    config.validate("", &mut problems);
+    for problem in problems {
+        warn!(
+            "The item in the app profile `{}` is invalid. {}",
+            name, problem
+        );
+    }
+    Some(config)
+}
+
+//noinspection StructuralWrap
+/**
+ * 把修改后的合并配置拆分回基础配置，应用程序配置覆盖的项目写回应用程序配置，其他的项目写入基础配置，返回新的基础配置。
+ * `config` 修改后的合并配置。
+ * `base` 修改前的基础配置。
+ * `name` 应用程序配置的名称。
+ * */
+pub(crate) fn split_profile(
+    config: &ConfigRoot,
+    base: &ConfigRoot,
+    name: &str,
+) -> Option<ConfigRoot> {
+    let overlay = get_overlay(base, name)?;
+    let merged = Table::try_from(config).ok()?;
+    let base_table = Table::try_from(base).ok()?;
+    let (table, overlay) = split_overlay(&merged, &base_table, &overlay);
+    let mut result: ConfigRoot = Value::Table(table).try_into().ok()?;
+    if let Some(profile) = result.app_profiles_config.profiles.get_mut(name) {
+        *profile = overlay;
+    }
+    Some(result)
+}
+
+// 获取应用程序配置中可以覆盖的项目
+fn get_overlay(base: &ConfigRoot, name: &str) -> Option<Table> {
+    let mut overlay = base.app_profiles_config.profiles.get(name)?.clone();
+    for key in IGNORED_KEYS {
+        overlay.remove(key);
+    }
+    Some(overlay)
+}
+
+// 把覆盖的项目递归地合并到表格中，数组等其他类型的值会被整个替换
+fn merge_overlay(table: &mut Table, overlay: &Table) {
+    for (key, value) in overlay {
+        match (table.get_mut(key), value) {
+            (Some(Value::Table(t)), Value::Table(o)) => merge_overlay(t, o),
+            _ => {
+                table.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+// 按照覆盖的项目拆分合并后的表格，返回（基础的表格，覆盖的表格）
+fn split_overlay(merged: &Table, base: &Table, overlay: &Table) -> (Table, Table) {
+    let mut new_base = merged.clone();
+    let mut new_overlay = Table::new();
+
+    for (key, value) in overlay {
+        match (value, merged.get(key)) {
+            (Value::Table(o), Some(Value::Table(m))) => {
+                let empty = Table::new();
+                let b = match base.get(key) {
+                    Some(Value::Table(b)) => b,
+                    _ => &empty,
+                };
+                let (b, o) = split_overlay(m, b, o);
+                new_base.insert(key.clone(), Value::Table(b));
+                new_overlay.insert(key.clone(), Value::Table(o));
+            }
+            (_, m) => {
+                // 被覆盖的值属于应用程序配置，基础配置保持原来的值
+                if let Some(m) = m {
+                    new_overlay.insert(key.clone(), m.clone());
+                }
+                match base.get(key) {
+                    Some(b) => new_base.insert(key.clone(), b.clone()),
+                    None => new_base.remove(key),
+                };
+            }
+        }
+    }
+
+    (new_base, new_overlay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(s: &str) -> Table {
+        toml::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_merge_overlay() {
+        let mut base = table("[tts]\nspeed = 50\npitch = 50\n[mouse]\nis_read = false\n");
+        merge_overlay(
+            &mut base,
+            &table("[tts]\nspeed = 80\n[mouse]\nis_read = true\n"),
+        );
+        assert_eq!(
+            base,
+            table("[tts]\nspeed = 80\npitch = 50\n[mouse]\nis_read = true\n")
+        );
+    }
+
+    #[test]
+    fn test_apply_profile_validate() {
+        let mut base = ConfigRoot::default();
+        base.app_profiles_config.profiles.insert(
+            "code.exe".to_string(),
+            table("[tts_config]\nspeed = 500\npitch = 60\n"),
+        );
+        let config = apply_profile(&base, "code.exe").unwrap();
+        assert_eq!(config.tts_config.speed, 100);
+        assert_eq!(config.tts_config.pitch, 60);
+    }
+
+    #[test]
+    fn test_split_overlay() {
+        let base = table("[tts]\nspeed = 50\npitch = 50\n");
+        let overlay = table("[tts]\nspeed = 80\n");
+        // 在应用程序中修改了语速和音调
+        let merged = table("[tts]\nspeed = 90\npitch = 60\n");
+        let (base, overlay) = split_overlay(&merged, &base, &overlay);
+        assert_eq!(base, table("[tts]\nspeed = 50\npitch = 60\n"));
+        assert_eq!(overlay, table("[tts]\nspeed = 90\n"));
+    }
+}
@@ -0,0 +1,581 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::configs::{
+    items::{
+        sound::SoundConfig, speech_dict::SpeechDictConfig, tts::TtsConfig,
+        voice_profile::VoiceProfilesConfig,
+    },
+    ConfigRoot,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::HashSet,
+    fmt::{Display, Formatter},
+    ops::RangeInclusive,
+};
+use toml::{Table, Value};
+
+/// 分段朗读时每一段最少的字符数，太短的分段会让朗读断断续续
+const MIN_SEGMENT_LENGTH: usize = 10;
+
+/// 配置中发现的一个问题
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ConfigProblem {
+    /// 配置项的路径，例如tts_config.speed
+    pub(crate) path: String,
+    /// 配置项原来的值
+    pub(crate) value: String,
+    /// 问题的原因以及如何处理的
+    pub(crate) reason: String,
+}
+
+impl ConfigProblem {
+    fn new(path: &str, value: impl Display, reason: impl Into<String>) -> Self {
+        Self {
+            path: path.to_string(),
+            value: value.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl Display for ConfigProblem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {}: {}", self.path, self.value, self.reason)
+    }
+}
+
+/// 检查配置项的值
+pub(crate) trait Validate {
+    //noinspection StructuralWrap
+    /**
+     * 检查配置项，把超出范围的值修正为有效的值，删除无法使用的条目，并记录发现的问题。
+     * `path` 配置项的路径。
+     * `problems` 发现的问题。
+     * */
+    fn validate(&mut self, path: &str, problems: &mut Vec<ConfigProblem>);
+}
+
+impl Validate for ConfigRoot {
+    fn validate(&mut self, path: &str, problems: &mut Vec<ConfigProblem>) {
+        self.tts_config
+            .validate(&join(path, "tts_config"), problems);
+        self.sound_config
+            .validate(&join(path, "sound_config"), problems);
+        self.voice_profiles_config
+            .validate(&join(path, "voice_profiles_config"), problems);
+        self.speech_dict_config
+            .validate(&join(path, "speech_dict_config"), problems);
+    }
+}
+
+impl Validate for TtsConfig {
+    fn validate(&mut self, path: &str, problems: &mut Vec<ConfigProblem>) {
+        clamp(&mut self.speed, 0..=100, &join(path, "speed"), problems);
+        clamp(&mut self.volume, 0..=100, &join(path, "volume"), problems);
+        clamp(&mut self.pitch, 0..=100, &join(path, "pitch"), problems);
+        clamp(
+            &mut self.capital.pitch_change,
+            0..=100,
+            &join(path, "capital.pitch_change"),
+            problems,
+        );
+        clamp(
+            &mut self.pcm_cache.max_chars,
+            1..=100,
+            &join(path, "pcm_cache.max_chars"),
+            problems,
+        );
+        clamp(
+            &mut self.pcm_cache.max_memory,
+            0..=1024 * 1024,
+            &join(path, "pcm_cache.max_memory"),
+            problems,
+        );
+        // 0表示不分段
+        if self.segment_length != 0 {
+            clamp(
+                &mut self.segment_length,
+                MIN_SEGMENT_LENGTH..=usize::MAX,
+                &join(path, "segment_length"),
+                problems,
+            );
+        }
+
+        let mut names = HashSet::new();
+        let engines = std::mem::take(&mut self.command_engines);
+        for (i, engine) in engines.into_iter().enumerate() {
+            let item = join(path, &format!("command_engines[{}]", i));
+            let reason = if engine.name.trim().is_empty() {
+                "the engine name is empty"
+            } else if engine.program.trim().is_empty() {
+                "the program is empty"
+            } else if engine.sample_rate == 0 {
+                "the sample rate is zero"
+            } else if !(1..=2).contains(&engine.channels) {
+                "the channels must be 1 or 2"
+            } else if !names.insert(engine.name.clone()) {
+                "the engine name is duplicated"
+            } else {
+                self.command_engines.push(engine);
+                continue;
+            };
+            problems.push(ConfigProblem::new(
+                &item,
+                &engine.name,
+                format!("{}, the engine is removed", reason),
+            ));
+        }
+
+        self.language_voices.retain(|script, (engine, id)| {
+            if !engine.trim().is_empty() && !id.trim().is_empty() {
+                return true;
+            }
+            problems.push(ConfigProblem::new(
+                &join(path, &format!("language_voices.{:?}", script)),
+                format!("[\"{}\", \"{}\"]", engine, id),
+                "the engine or the voice is empty, the entry is removed",
+            ));
+            false
+        });
+
+        // 参数的取值范围由引擎提供，应用到引擎时才能检查，这里只删除无法对应到引擎参数的条目
+        self.engine_params.retain(|engine, params| {
+            if engine.trim().is_empty() {
+                problems.push(ConfigProblem::new(
+                    &join(path, "engine_params"),
+                    "\"\"",
+                    "the engine name is empty, the entry is removed",
+                ));
+                return false;
+            }
+            params.retain(|name, value| {
+                if !name.trim().is_empty() {
+                    return true;
+                }
+                problems.push(ConfigProblem::new(
+                    &join(path, &format!("engine_params.{}", engine)),
+                    value,
+                    "the param name is empty, the entry is removed",
+                ));
+                false
+            });
+            true
+        });
+    }
+}
+
+impl Validate for SoundConfig {
+    fn validate(&mut self, path: &str, problems: &mut Vec<ConfigProblem>) {
+        for (event, item) in self.items.iter_mut() {
+            let item_path = join(path, &format!("items.{:?}", event));
+            clamp(
+                &mut item.volume,
+                0..=100,
+                &join(&item_path, "volume"),
+                problems,
+            );
+            clamp(
+                &mut item.pan,
+                -100..=100,
+                &join(&item_path, "pan"),
+                problems,
+            );
+        }
+    }
+}
+
+impl Validate for VoiceProfilesConfig {
+    fn validate(&mut self, path: &str, problems: &mut Vec<ConfigProblem>) {
+        for (i, profile) in self.profiles.iter_mut().enumerate() {
+            profile.tts_config.validate(
+                &join(path, &format!("profiles[{}].tts_config", i)),
+                problems,
+            );
+        }
+    }
+}
+
+impl Validate for SpeechDictConfig {
+    fn validate(&mut self, path: &str, problems: &mut Vec<ConfigProblem>) {
+        let mut i = 0;
+        self.entries.retain(|e| {
+            let item = join(path, &format!("entries[{}].pattern", i));
+            i += 1;
+            if !e.pattern.is_empty() {
+                return true;
+            }
+            problems.push(ConfigProblem::new(
+                &item,
+                "\"\"",
+                "the pattern is empty, the entry is removed",
+            ));
+            false
+        });
+    }
+}
+
+//noinspection StructuralWrap
+/**
+ * 宽松地解析配置文档，无法解析的配置项（例如错误的类型或者不认识的枚举值）使用默认值并记录问题，而不是丢弃整个文档。
+ * 返回解析的结果和配置结构不认识的项目，写入配置文件时应该保留这些项目，这样新版本的配置文件被旧版本的程序修改后不会丢失配置项。
+ * `doc` 配置文档。
+ * `problems` 发现的问题。
+ * */
+pub(crate) fn parse_lenient<T>(doc: &Table, problems: &mut Vec<ConfigProblem>) -> (T, Table)
+where
+    T: Default + Serialize + DeserializeOwned,
+{
+    let parse = |t: &Table| Value::Table(t.clone()).try_into::<T>();
+    let (value, valid) = match parse(doc) {
+        Ok(v) => (v, doc.clone()),
+        Err(_) => {
+            // 从默认值开始，把文档中的值逐个放进去，放进去后无法解析的值保持默认值
+            let mut valid = Table::try_from(T::default()).unwrap_or_default();
+            fill(&mut valid, doc, &mut vec![], &parse, problems);
+            let value = parse(&valid).unwrap_or_default();
+            (value, valid)
+        }
+    };
+
+    let known = Table::try_from(&value).unwrap_or_default();
+    (value, get_unknown(&valid, &known))
+}
+
+//noinspection StructuralWrap
+/**
+ * 把配置结构不认识的项目合并到要写入的表格中，表格中已经存在的项目不会被替换。
+ * `table` 要写入的表格。
+ * `unknown` 不认识的项目。
+ * */
+pub(crate) fn merge_unknown(table: &mut Table, unknown: &Table) {
+    for (key, value) in unknown {
+        match (table.get_mut(key), value) {
+            (None, _) => {
+                table.insert(key.clone(), value.clone());
+            }
+            (Some(Value::Table(t)), Value::Table(u)) => merge_unknown(t, u),
+            _ => {}
+        }
+    }
+}
+
+// 把文档中的值逐个放入有效的表格，表格中只有部分值无法解析时，逐个检查表格中的值
+fn fill<T>(
+    root: &mut Table,
+    doc: &Table,
+    path: &mut Vec<String>,
+    parse: &impl Fn(&Table) -> Result<T, toml::de::Error>,
+    problems: &mut Vec<ConfigProblem>,
+) {
+    for (key, value) in doc {
+        path.push(key.clone());
+        let old = get_at(root, path).cloned();
+        set_at(root, path, Some(value.clone()));
+
+        if let Err(e) = parse(root) {
+            let base = match &old {
+                Some(Value::Table(t)) => Some(t.clone()),
+                None => Some(Table::new()),
+                _ => None,
+            };
+            match (value, base) {
+                (Value::Table(t), Some(base)) => {
+                    set_at(root, path, Some(Value::Table(base)));
+                    if parse(root).is_ok() {
+                        fill(root, t, path, parse, problems);
+                    } else {
+                        set_at(root, path, old);
+                        problems.push(invalid(path, value, &e));
+                    }
+                }
+                (Value::Array(a), _) => {
+                    set_at(root, path, Some(Value::Array(vec![])));
+                    if parse(root).is_ok() {
+                        fill_array(root, a, path, parse, problems);
+                    } else {
+                        set_at(root, path, old);
+                        problems.push(invalid(path, value, &e));
+                    }
+                }
+                _ => {
+                    set_at(root, path, old);
+                    problems.push(invalid(path, value, &e));
+                }
+            }
+        }
+        path.pop();
+    }
+}
+
+// 把数组中的元素逐个放入有效的表格，只丢弃无法解析的元素，这样一个错误的条目不会让整个数组恢复成默认值
+fn fill_array<T>(
+    root: &mut Table,
+    items: &[Value],
+    path: &mut Vec<String>,
+    parse: &impl Fn(&Table) -> Result<T, toml::de::Error>,
+    problems: &mut Vec<ConfigProblem>,
+) {
+    let mut valid = vec![];
+    for (i, item) in items.iter().enumerate() {
+        valid.push(item.clone());
+        set_at(root, path, Some(Value::Array(valid.clone())));
+        if let Err(e) = parse(root) {
+            valid.pop();
+            path.push(i.to_string());
+            problems.push(ConfigProblem::new(
+                &path.join("."),
+                item,
+                format!("{}, the item is removed", e.message()),
+            ));
+            path.pop();
+        }
+    }
+    set_at(root, path, Some(Value::Array(valid)));
+}
+
+// 获取文档中有但是配置结构中没有的项目
+fn get_unknown(doc: &Table, known: &Table) -> Table {
+    let mut unknown = Table::new();
+    for (key, value) in doc {
+        match (value, known.get(key)) {
+            (_, None) => {
+                unknown.insert(key.clone(), value.clone());
+            }
+            (Value::Table(d), Some(Value::Table(k))) => {
+                let t = get_unknown(d, k);
+                if !t.is_empty() {
+                    unknown.insert(key.clone(), Value::Table(t));
+                }
+            }
+            _ => {}
+        }
+    }
+    unknown
+}
+
+fn get_at<'a>(table: &'a Table, path: &[String]) -> Option<&'a Value> {
+    let (last, parents) = path.split_last()?;
+    let mut table = table;
+    for key in parents {
+        table = table.get(key)?.as_table()?;
+    }
+    table.get(last)
+}
+
+// 设置指定路径的值，值是None时删除，路径上的表格必须已经存在
+fn set_at(table: &mut Table, path: &[String], value: Option<Value>) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut table = table;
+    for key in parents {
+        let Some(Value::Table(t)) = table.get_mut(key) else {
+            return;
+        };
+        table = t;
+    }
+    match value {
+        Some(v) => table.insert(last.clone(), v),
+        None => table.remove(last),
+    };
+}
+
+fn invalid(path: &[String], value: &Value, error: &toml::de::Error) -> ConfigProblem {
+    ConfigProblem::new(
+        &path.join("."),
+        value,
+        format!("{}, using the default value", error.message()),
+    )
+}
+
+fn clamp<T>(value: &mut T, range: RangeInclusive<T>, path: &str, problems: &mut Vec<ConfigProblem>)
+where
+    T: PartialOrd + Copy + Display,
+{
+    let (min, max) = (*range.start(), *range.end());
+    let clamped = if *value < min {
+        min
+    } else if *value > max {
+        max
+    } else {
+        return;
+    };
+    problems.push(ConfigProblem::new(
+        path,
+        *value,
+        format!("out of range, changed to {}", clamped),
+    ));
+    *value = clamped;
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configs::items::tts::Script;
+    use serde::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Default)]
+    enum Mode {
+        #[default]
+        A,
+        B,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq, Default)]
+    #[serde(default)]
+    struct Section {
+        speed: i32,
+        mode: Mode,
+        keys: HashMap<String, Mode>,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Entry {
+        name: String,
+        mode: Mode,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq, Default)]
+    #[serde(default)]
+    struct Root {
+        section: Section,
+        entries: Vec<Entry>,
+    }
+
+    fn table(s: &str) -> Table {
+        toml::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let doc = table(
+            "future = 1\n[section]\nspeed = 80\nmode = \"C\"\nnew_item = true\n[section.keys]\na = \"B\"\nb = 3\n",
+        );
+        let mut problems = vec![];
+        let (root, unknown) = parse_lenient::<Root>(&doc, &mut problems);
+        // 错误的枚举值和映射中错误的条目不影响其他的配置项
+        assert_eq!(root.section.speed, 80);
+        assert_eq!(root.section.mode, Mode::A);
+        assert_eq!(
+            root.section.keys,
+            HashMap::from([("a".to_string(), Mode::B)])
+        );
+        let paths = problems.iter().map(|p| p.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["section.keys.b", "section.mode"]);
+        assert_eq!(problems[1].value, "\"C\"");
+
+        // 不认识的配置项会保留下来，无法解析的值不会保留
+        assert_eq!(unknown, table("future = 1\n[section]\nnew_item = true\n"));
+        let mut written = Table::try_from(&root).unwrap();
+        merge_unknown(&mut written, &unknown);
+        assert_eq!(written["future"].as_integer(), Some(1));
+        assert_eq!(written["section"]["new_item"].as_bool(), Some(true));
+        assert_eq!(written["section"]["speed"].as_integer(), Some(80));
+    }
+
+    #[test]
+    fn test_parse_lenient_array() {
+        let doc = table(
+            "[[entries]]\nname = \"a\"\nmode = \"B\"\n[[entries]]\nname = \"b\"\nmode = \"C\"\n[[entries]]\nname = \"c\"\nmode = \"A\"\n",
+        );
+        let mut problems = vec![];
+        let (root, unknown) = parse_lenient::<Root>(&doc, &mut problems);
+        // 只丢弃错误的条目，其他的条目保持不变
+        let names = root
+            .entries
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a", "c"]);
+        assert_eq!(root.entries[0].mode, Mode::B);
+        let paths = problems.iter().map(|p| p.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, vec!["entries.1"]);
+        assert!(unknown.is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut config = TtsConfig {
+            speed: 500,
+            volume: -3,
+            segment_length: 3,
+            ..Default::default()
+        };
+        let mut problems = vec![];
+        config.validate("tts_config", &mut problems);
+        assert_eq!((config.speed, config.volume), (100, 0));
+        assert_eq!(config.segment_length, MIN_SEGMENT_LENGTH);
+        assert_eq!(
+            problems[0].to_string(),
+            "tts_config.speed = 500: out of range, changed to 100"
+        );
+        assert_eq!(problems.len(), 3);
+
+        // 有效的配置不会产生问题
+        let mut problems = vec![];
+        TtsConfig::default().validate("tts_config", &mut problems);
+        assert!(problems.is_empty());
+    }
+
+    #[test]
+    fn test_validate_voices_and_params() {
+        let mut config = TtsConfig::default();
+        config
+            .language_voices
+            .insert(Script::Latin, ("Sapi5".to_string(), "David".to_string()));
+        config
+            .language_voices
+            .insert(Script::Han, ("".to_string(), "Huihui".to_string()));
+        config.engine_params.insert(
+            "Vvtts".to_string(),
+            HashMap::from([("gender".to_string(), 1), (" ".to_string(), 5)]),
+        );
+        config
+            .engine_params
+            .insert("".to_string(), HashMap::from([("gender".to_string(), 1)]));
+        let mut problems = vec![];
+        config.validate("tts_config", &mut problems);
+
+        // 只删除无效的条目，其他的条目保持不变
+        assert_eq!(config.language_voices.len(), 1);
+        assert!(config.language_voices.contains_key(&Script::Latin));
+        assert_eq!(
+            config.engine_params,
+            HashMap::from([(
+                "Vvtts".to_string(),
+                HashMap::from([("gender".to_string(), 1)])
+            )])
+        );
+        let mut paths = problems.iter().map(|p| p.path.as_str()).collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                "tts_config.engine_params",
+                "tts_config.engine_params.Vvtts",
+                "tts_config.language_voices.Han"
+            ]
+        );
+    }
+}
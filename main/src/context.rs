@@ -133,8 +133,12 @@ impl Context {
         // 初始化命令管理器
         self.commander.apply(ctx.clone());
 
-        // 加载配置文件
+        // 加载配置文件，启动监视和延时写入配置文件的后台任务，退出时写入还没有保存的修改
         self.config_manager.apply();
+        self.config_manager.start(self.work_runtime);
+        let config_manager = self.config_manager.clone();
+        self.terminator
+            .add_exiting_listener(move || config_manager.flush());
 
         // 启动表演者
         let performer = self.performer.clone();
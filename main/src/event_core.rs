@@ -17,6 +17,7 @@ mod element;
 mod focus;
 mod ime;
 mod input;
+mod profile;
 mod progress;
 
 use std::{
@@ -33,7 +34,7 @@ use crate::{
     event_core::{
         dialog::subscribe_dialog_events, element::subscribe_element_events,
         focus::subscribe_focus_events, ime::subscribe_ime_events, input::subscribe_input_events,
-        progress::subscribe_progress_and_slider_events,
+        profile::subscribe_profile_events, progress::subscribe_progress_and_slider_events,
     },
 };
 
@@ -107,6 +108,9 @@ impl EventCore {
 
         // 订阅进度栏事件
         subscribe_progress_and_slider_events(context.clone()).await;
+
+        // 订阅应用程序配置的切换事件
+        subscribe_profile_events(context.clone()).await;
     }
 
     /**
@@ -12,9 +12,13 @@
  */
 
 use crate::{
+    configs::items::sound::SoundEvent,
     context::{Context, ContextAccessor},
     ext::dialog::AccessibleDialogExt,
-    performer::sound::SoundArgument::Single,
+    performer::{
+        scheduler::{SpeechChannel, SpeechPriority},
+        sound::SoundArgument::Single,
+    },
 };
 use std::{sync::Weak, time::Duration};
 use tokio::time::sleep;
@@ -23,7 +27,7 @@ use win_wrap::msaa::{event::WinEventSource, object::AccessibleObject};
 pub(crate) fn handle_dialog_events(context: Weak<Context>, src: WinEventSource) {
     let ctx = context.clone();
     context.get_work_runtime().spawn(async move {
-        // 延迟朗读，防止被焦点元素打断。
+        // 延迟朗读，让焦点元素先进入朗读队列。
         sleep(Duration::from_millis(500)).await;
 
         let obj = match src.get_object() {
@@ -33,8 +37,16 @@ pub(crate) fn handle_dialog_events(context: Weak<Context>, src: WinEventSource)
             },
             Ok(o) => o.0,
         };
-        ctx.get_performer().play_sound(Single("dialog.wav")).await;
-        ctx.get_performer().speak(&obj.get_dialog_content()).await;
+        ctx.get_performer()
+            .play_sound(Single(SoundEvent::Dialog))
+            .await;
+        ctx.get_performer()
+            .speak_with(
+                &obj.get_dialog_content(),
+                SpeechPriority::Queue,
+                SpeechChannel::Dialog,
+            )
+            .await;
     });
 }
 
@@ -13,9 +13,17 @@
 
 use crate::{
     commander::keyboard::keys::Keys::{self, VkDown, VkLeft, VkRight, VkUp},
+    configs::items::sound::SoundEvent,
     context::{Context, ContextAccessor},
-    ext::element::UiAutomationElementExt,
-    performer::sound::SoundArgument::{self, Single},
+    ext::{
+        element::UiAutomationElementExt,
+        text::{Ia2TextSource, JabTextSource, UiaTextSource},
+    },
+    performer::{
+        say_all::TextSource,
+        scheduler::{SpeechChannel, SpeechPriority},
+        sound::SoundArgument::{self, Single},
+    },
 };
 use a11y::{
     ia2::{
@@ -103,6 +111,21 @@ impl Editor {
         control.store(Control::None.into());
     }
 
+    /**
+    获取当前编辑框的文本来源，用于连续朗读，没有编辑框时返回None。
+    */
+    pub(crate) fn get_text_source(&self) -> Option<Box<dyn TextSource>> {
+        let control: Control = self.control.load().deref().deref().clone();
+        match control {
+            Control::None => None,
+            Control::Uia(element) => {
+                UiaTextSource::new(&element).map(|s| Box::new(s) as Box<dyn TextSource>)
+            }
+            Control::Ia2(text) => Some(Box::new(Ia2TextSource(text))),
+            Control::Jab(src, _) => Some(Box::new(JabTextSource(src))),
+        }
+    }
+
     // 订阅 msaa 编辑器事件
     async fn subscribe_msaa_events(&self) {
         let context = self.context.get().unwrap();
@@ -120,7 +143,7 @@ impl Editor {
             };
             let control = WindowControl::from(obj.window());
             let (start, _end) = control.get_sel();
-            let sound = SoundArgument::WithFreq("progress.wav", (start * 10 + 400) as f32);
+            let sound = SoundArgument::WithFreq(SoundEvent::Progress, (start * 10 + 400) as f32);
 
             let ctx2 = ctx.clone();
             ctx.get_work_runtime().spawn(async move {
@@ -157,8 +180,16 @@ impl Editor {
                 }
 
                 match mng.get_last_pressed_key() {
-                    VkUp | VkDown => ctx2.get_performer().speak(&line).await,
-                    _ => ctx2.get_performer().speak(&char).await,
+                    VkUp | VkDown => {
+                        ctx2.get_performer()
+                            .speak_with(&line, SpeechPriority::Interrupt, SpeechChannel::Caret)
+                            .await
+                    }
+                    _ => {
+                        ctx2.get_performer()
+                            .speak_with(&char, SpeechPriority::Interrupt, SpeechChannel::Caret)
+                            .await
+                    }
                 };
             });
         };
@@ -210,10 +241,18 @@ impl Editor {
 
                 match is_blank_line {
                     true => {
-                        ctx2.get_performer().speak(&("空航".to_string())).await;
+                        ctx2.get_performer()
+                            .speak_with(
+                                &("空航".to_string()),
+                                SpeechPriority::Interrupt,
+                                SpeechChannel::Caret,
+                            )
+                            .await;
                     }
                     false => {
-                        ctx2.get_performer().speak(&caret).await;
+                        ctx2.get_performer()
+                            .speak_with(&caret, SpeechPriority::Interrupt, SpeechChannel::Caret)
+                            .await;
                     }
                 }
             });
@@ -263,7 +302,9 @@ impl Editor {
                 if ec.should_ignore(text.clone(), DURATION).await {
                     return;
                 }
-                ctx2.get_performer().speak(&text).await;
+                ctx2.get_performer()
+                    .speak_with(&text, SpeechPriority::Interrupt, SpeechChannel::Caret)
+                    .await;
             });
         };
 
@@ -304,13 +345,27 @@ impl Editor {
 
                     let ctx2 = ctx.clone();
                     ctx.get_work_runtime().spawn(async move {
-                        ctx2.get_performer().play_sound(Single("edge.wav")).await;
+                        ctx2.get_performer()
+                            .play_sound(Single(SoundEvent::Edge))
+                            .await;
                         match is_blank_line {
                             true => {
-                                ctx2.get_performer().speak(&"空航".to_string()).await;
+                                ctx2.get_performer()
+                                    .speak_with(
+                                        &"空航".to_string(),
+                                        SpeechPriority::Interrupt,
+                                        SpeechChannel::Caret,
+                                    )
+                                    .await;
                             }
                             false => {
-                                ctx2.get_performer().speak(&caret).await;
+                                ctx2.get_performer()
+                                    .speak_with(
+                                        &caret,
+                                        SpeechPriority::Interrupt,
+                                        SpeechChannel::Caret,
+                                    )
+                                    .await;
                             }
                         }
                     });
@@ -345,7 +400,9 @@ impl Editor {
                         if ec.should_ignore(text.clone(), DURATION).await {
                             return;
                         }
-                        ctx2.get_performer().speak(&text).await;
+                        ctx2.get_performer()
+                            .speak_with(&text, SpeechPriority::Interrupt, SpeechChannel::Caret)
+                            .await;
                     });
                 }
 
@@ -377,8 +434,24 @@ impl Editor {
                             return;
                         }
                         match key {
-                            VkUp | VkDown => ctx2.get_performer().speak(&line).await,
-                            _ => ctx2.get_performer().speak(&char).await,
+                            VkUp | VkDown => {
+                                ctx2.get_performer()
+                                    .speak_with(
+                                        &line,
+                                        SpeechPriority::Interrupt,
+                                        SpeechChannel::Caret,
+                                    )
+                                    .await
+                            }
+                            _ => {
+                                ctx2.get_performer()
+                                    .speak_with(
+                                        &char,
+                                        SpeechPriority::Interrupt,
+                                        SpeechChannel::Caret,
+                                    )
+                                    .await
+                            }
                         };
                     });
                 }
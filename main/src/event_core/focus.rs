@@ -12,9 +12,13 @@
  */
 
 use crate::{
+    configs::items::sound::SoundEvent,
     context::{Context, ContextAccessor},
     event_core::{dialog::handle_dialog_events, ime::MS_IME_CLASS_NAME},
-    performer::sound::SoundArgument::Single,
+    performer::{
+        scheduler::{SpeechChannel, SpeechPriority},
+        sound::SoundArgument::Single,
+    },
 };
 use std::{sync::Weak, time::Duration};
 use win_wrap::{
@@ -48,7 +52,9 @@ pub(crate) async fn subscribe_focus_events(context: Weak<Context>) {
                     _ => {}
                 }
 
-                ctx2.get_performer().speak(&x).await;
+                ctx2.get_performer()
+                    .speak_with(&x, SpeechPriority::Interrupt, SpeechChannel::Focus)
+                    .await;
             });
         });
 
@@ -82,7 +88,13 @@ pub(crate) async fn subscribe_focus_events(context: Weak<Context>) {
                 // 过滤重复的事件，因为同时订阅了UIA和MSAA的focus事件，就会有事件的重复
                 return;
             }
-            ctx2.get_performer().speak(&(obj, child)).await;
+            ctx2.get_performer()
+                .speak_with(
+                    &(obj, child),
+                    SpeechPriority::Interrupt,
+                    SpeechChannel::Focus,
+                )
+                .await;
         });
     });
 
@@ -110,7 +122,13 @@ pub(crate) async fn subscribe_focus_events(context: Weak<Context>) {
                     return;
                 }
 
-                ctx2.get_performer().speak(&(obj, child)).await;
+                ctx2.get_performer()
+                    .speak_with(
+                        &(obj, child),
+                        SpeechPriority::Interrupt,
+                        SpeechChannel::Focus,
+                    )
+                    .await;
             });
         });
 
@@ -125,8 +143,12 @@ pub(crate) async fn subscribe_focus_events(context: Weak<Context>) {
         };
         let ctx2 = ctx.clone();
         ctx.get_work_runtime().spawn(async move {
-            ctx2.get_performer().play_sound(Single("tip.wav")).await;
-            ctx2.get_performer().speak(&obj).await;
+            ctx2.get_performer()
+                .play_sound(Single(SoundEvent::Tip))
+                .await;
+            ctx2.get_performer()
+                .speak_with(&obj, SpeechPriority::Queue, SpeechChannel::General)
+                .await;
         });
     });
 
@@ -135,7 +157,9 @@ pub(crate) async fn subscribe_focus_events(context: Weak<Context>) {
     context.get_jab().add_on_focus_gained_listener(move |src| {
         let ctx2 = ctx.clone();
         ctx.get_work_runtime().spawn(async move {
-            ctx2.get_performer().speak(&src).await;
+            ctx2.get_performer()
+                .speak_with(&src, SpeechPriority::Interrupt, SpeechChannel::Focus)
+                .await;
         });
     });
 
@@ -146,7 +170,9 @@ pub(crate) async fn subscribe_focus_events(context: Weak<Context>) {
         .add_on_property_active_descendent_change_listener(move |_, _, new| {
             let ctx2 = ctx.clone();
             ctx.get_work_runtime().spawn(async move {
-                ctx2.get_performer().speak(&new).await;
+                ctx2.get_performer()
+                    .speak_with(&new, SpeechPriority::Interrupt, SpeechChannel::Focus)
+                    .await;
             });
         });
 }
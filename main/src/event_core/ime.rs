@@ -17,8 +17,12 @@ use win_wrap::input::{IME_CMODE_ALPHANUMERIC, IME_CMODE_FULLSHAPE, IME_CMODE_NAT
 
 use crate::{
     cancel_edge_handle,
+    configs::items::sound::SoundEvent,
     context::{Context, ContextAccessor},
-    performer::sound::SoundArgument::Single,
+    performer::{
+        scheduler::{SpeechChannel, SpeechPriority},
+        sound::SoundArgument::Single,
+    },
 };
 
 pub(crate) const MS_IME_CLASS_NAME: &str = "Windows.UI.Core.CoreWindow";
@@ -54,8 +58,11 @@ pub(crate) async fn subscribe_ime_events(context: Weak<Context>) {
             };
 
             let ctx2 = ctx.clone();
-            ctx.get_work_runtime()
-                .spawn(async move { ctx2.get_performer().speak(&mode).await });
+            ctx.get_work_runtime().spawn(async move {
+                ctx2.get_performer()
+                    .speak_with(&mode, SpeechPriority::Interrupt, SpeechChannel::Ime)
+                    .await
+            });
         })
         .await;
 
@@ -95,7 +102,15 @@ fn handle_ime_candidate(context: Weak<Context>, candidate_list: CandidateList) {
             if candidate.is_empty() {
                 return;
             }
-            if !ctx.get_performer().speak(&candidate_list.clone()).await {
+            if !ctx
+                .get_performer()
+                .speak_with(
+                    &candidate_list.clone(),
+                    SpeechPriority::Interrupt,
+                    SpeechChannel::Ime,
+                )
+                .await
+            {
                 // 如果语音被打断就不继续朗读候选的解释词
                 return;
             }
@@ -103,9 +118,13 @@ fn handle_ime_candidate(context: Weak<Context>, candidate_list: CandidateList) {
                 return;
             };
             if let Some(x) = unsafe { &*cache.as_ptr() }.make_word(&candidate) {
-                ctx.get_performer().play_sound(Single("tip.wav")).await;
-                // 朗读候选文字的解释词
-                ctx.get_performer().speak(x).await;
+                ctx.get_performer()
+                    .play_sound(Single(SoundEvent::Tip))
+                    .await;
+                // 朗读候选文字的解释词，排在候选文字之后，不打断其他语音
+                ctx.get_performer()
+                    .speak_with(x, SpeechPriority::Queue, SpeechChannel::Ime)
+                    .await;
             }
         }),
     );
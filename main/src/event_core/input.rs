@@ -14,6 +14,7 @@
 use crate::{
     commander::keyboard::keys::Keys::{VkNumlock, VkScroll},
     context::{Context, ContextAccessor},
+    performer::scheduler::{SpeechChannel, SpeechPriority},
 };
 use std::sync::Weak;
 use win_wrap::input::{get_key_state, VK_NUMLOCK, VK_SCROLL};
@@ -35,7 +36,9 @@ pub(crate) async fn subscribe_input_events(context: Weak<Context>) {
             ctx.get_task_manager().abort("ime");
 
             ctx.get_work_runtime().spawn(async move {
-                ctx2.get_performer().speak(&c).await;
+                ctx2.get_performer()
+                    .speak_with(&c, SpeechPriority::Interrupt, SpeechChannel::Input)
+                    .await;
             });
         })
         .await;
@@ -0,0 +1,41 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::{
+    context::{Context, ContextAccessor},
+    ext::window::get_foreground_process_name,
+};
+use log::info;
+use std::sync::Weak;
+
+/**
+订阅前景窗口改变事件，切换到前景窗口的应用程序的配置。
+`context` 读屏框架的上下文环境。
+*/
+pub(crate) async fn subscribe_profile_events(context: Weak<Context>) {
+    let ctx = context.clone();
+    context
+        .get_msaa()
+        .add_on_system_foreground_listener(move |_| {
+            let ctx2 = ctx.clone();
+            ctx.get_work_runtime().spawn(async move {
+                let app = get_foreground_process_name();
+                let mng = ctx2.get_config_manager();
+                if !mng.switch_profile(app.as_deref()) {
+                    return;
+                }
+                // 语音参数和热键通过订阅配置的修改重新应用
+                info!("The app profile is switched to {:?}.", mng.get_profile());
+            });
+        });
+}
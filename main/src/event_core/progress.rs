@@ -12,6 +12,7 @@
  */
 
 use crate::{
+    configs::items::sound::SoundEvent,
     context::{Context, ContextAccessor},
     performer::sound::SoundArgument::WithFreq,
 };
@@ -47,7 +48,10 @@ pub(crate) async fn subscribe_progress_and_slider_events(context: Weak<Context>)
             let ctx2 = ctx.clone();
             ctx.get_work_runtime().spawn(async move {
                 ctx2.get_performer()
-                    .play_sound(WithFreq("progress.wav", 2000f32 + 460f32 * (value as f32)))
+                    .play_sound(WithFreq(
+                        SoundEvent::Progress,
+                        2000f32 + 460f32 * (value as f32),
+                    ))
                     .await;
             });
         });
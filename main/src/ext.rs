@@ -14,4 +14,5 @@
 pub(crate) mod dialog;
 pub(crate) mod element;
 pub(crate) mod role;
+pub(crate) mod text;
 pub(crate) mod window;
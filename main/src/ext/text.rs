@@ -0,0 +1,231 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::{ext::element::UiAutomationElementExt, performer::say_all::TextSource};
+use a11y::{ia2::text::AccessibleText, jab::callback::AccessibleContextType};
+use parking_lot::{Mutex, MutexGuard};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use win_wrap::uia::{
+    element::UiAutomationElement,
+    pattern::text::{TextPatternRangeEndpoint, TextUnit, UiAutomationTextRange},
+};
+
+/// IA2的文本来源
+#[derive(Debug)]
+pub(crate) struct Ia2TextSource(pub(crate) AccessibleText);
+
+unsafe impl Send for Ia2TextSource {}
+
+unsafe impl Sync for Ia2TextSource {}
+
+impl TextSource for Ia2TextSource {
+    fn get_length(&self) -> usize {
+        self.0.n_characters().max(0) as usize
+    }
+
+    fn get_text(&self, start: usize, end: usize) -> String {
+        self.0.text(start as i32, end as i32)
+    }
+
+    fn get_caret(&self) -> usize {
+        self.0.caret_offset().unwrap_or(0).max(0) as usize
+    }
+
+    fn set_caret(&self, offset: usize) -> bool {
+        self.0.set_caret_offset(offset as i32)
+    }
+}
+
+/// JAB的文本来源
+#[derive(Debug)]
+pub(crate) struct JabTextSource(pub(crate) AccessibleContextType);
+
+unsafe impl Send for JabTextSource {}
+
+unsafe impl Sync for JabTextSource {}
+
+impl TextSource for JabTextSource {
+    fn get_length(&self) -> usize {
+        self.0
+            .get_text_info(0, 0)
+            .map_or(0, |(count, _, _)| count.max(0) as usize)
+    }
+
+    fn get_text(&self, start: usize, end: usize) -> String {
+        get_inclusive_range(start, end, |start, end| self.0.get_text_range(start, end))
+    }
+
+    fn get_caret(&self) -> usize {
+        self.0
+            .get_text_info(0, 0)
+            .map_or(0, |(_, caret, _)| caret.max(0) as usize)
+    }
+
+    fn set_caret(&self, offset: usize) -> bool {
+        self.0.set_caret_position(offset as i32)
+    }
+}
+
+// JAB获取文本范围时包含结束位置的字符，所以结束位置需要减一，空的范围不需要获取
+fn get_inclusive_range(
+    start: usize,
+    end: usize,
+    get_range: impl Fn(i32, i32) -> Option<String>,
+) -> String {
+    if end <= start {
+        return String::new();
+    }
+    get_range(start as i32, end as i32 - 1).unwrap_or_default()
+}
+
+/// UIA每次读取文字时移动的字符数
+const UIA_FETCH_CHARS: i32 = 1000;
+
+//noinspection StructuralWrap
+/**
+ * UIA的文本来源。
+ * UIA的文本范围不能直接使用偏移量访问，所以所有的位置都从创建时的插入点开始计算，需要读取文字时才把文本范围按字符向后移动，不会一次读取整个文档。
+ * 创建时的插入点是位置0，通过set_caret移动以后，get_caret返回移动到的位置；用户在外部移动的插入点不会反映出来，需要重新创建文本来源。
+ * */
+#[derive(Debug)]
+pub(crate) struct UiaTextSource {
+    element: UiAutomationElement,
+    // 创建时插入点的位置，是一个空的文本范围
+    origin: UiAutomationTextRange,
+    // 已经读取的文字和下一次读取的位置，读到文档结尾以后位置是None
+    fetched: Mutex<(Vec<u16>, Option<UiAutomationTextRange>)>,
+    // 最后一次通过set_caret移动到的位置
+    caret: AtomicUsize,
+}
+
+unsafe impl Send for UiaTextSource {}
+
+unsafe impl Sync for UiaTextSource {}
+
+impl UiaTextSource {
+    /**
+     * 从UIA元素创建文本来源，元素不支持文本模式时返回None。
+     * `element` UIA元素。
+     * */
+    pub(crate) fn new(element: &UiAutomationElement) -> Option<Self> {
+        Some(Self {
+            element: element.clone(),
+            origin: Self::get_origin(element)?,
+            fetched: (vec![], Some(Self::get_origin(element)?)).into(),
+            caret: AtomicUsize::new(0),
+        })
+    }
+
+    // 获取插入点开始位置的空文本范围
+    // 每次获取插入点都会得到一个新的文本范围，修改他不会影响其他的范围
+    fn get_origin(element: &UiAutomationElement) -> Option<UiAutomationTextRange> {
+        let caret = element.get_caret()?;
+        let range = element.get_caret()?;
+        range.move_endpoint_by_range(
+            TextPatternRangeEndpoint::End,
+            &caret,
+            TextPatternRangeEndpoint::Start,
+        );
+        Some(range)
+    }
+
+    // 继续向后读取文字，直到读取的文字达到指定的长度或者读到文档的结尾
+    fn fetch_to(&self, length: usize) -> MutexGuard<'_, (Vec<u16>, Option<UiAutomationTextRange>)> {
+        let mut lock = self.fetched.lock();
+        while lock.0.len() < length {
+            let Some(range) = lock.1.as_ref() else {
+                break;
+            };
+            range.move_endpoint_by_unit(
+                TextPatternRangeEndpoint::End,
+                TextUnit::Character,
+                UIA_FETCH_CHARS,
+            );
+            let text = range.get_text(-1);
+            if text.is_empty() {
+                lock.1 = None;
+                break;
+            }
+            // 读取以后把范围收缩到结尾，下一次从这里继续读取
+            range.move_endpoint_by_range(
+                TextPatternRangeEndpoint::Start,
+                range,
+                TextPatternRangeEndpoint::End,
+            );
+            lock.0.extend(text.encode_utf16());
+        }
+        lock
+    }
+}
+
+impl TextSource for UiaTextSource {
+    fn get_length(&self) -> usize {
+        // 不读取整个文档就不能知道长度，所以返回上限，读到结尾以后获取的文字会变短
+        usize::MAX
+    }
+
+    fn get_text(&self, start: usize, end: usize) -> String {
+        let lock = self.fetch_to(end);
+        let end = end.min(lock.0.len());
+        String::from_utf16_lossy(&lock.0[start.min(end)..end])
+    }
+
+    fn get_caret(&self) -> usize {
+        self.caret.load(Ordering::Acquire)
+    }
+
+    fn set_caret(&self, offset: usize) -> bool {
+        let Some(range) = self.element.get_caret() else {
+            return false;
+        };
+
+        // UIA按照字符移动，而不是按照UTF-16编码单元
+        let count = self.get_text(0, offset).chars().count() as i32;
+        range.move_endpoint_by_range(
+            TextPatternRangeEndpoint::Start,
+            &self.origin,
+            TextPatternRangeEndpoint::Start,
+        );
+        range.move_endpoint_by_range(
+            TextPatternRangeEndpoint::End,
+            &self.origin,
+            TextPatternRangeEndpoint::Start,
+        );
+        range.move_endpoint_by_unit(TextPatternRangeEndpoint::End, TextUnit::Character, count);
+        range.move_endpoint_by_unit(TextPatternRangeEndpoint::Start, TextUnit::Character, count);
+        if range.select().is_err() {
+            return false;
+        }
+        self.caret.store(offset, Ordering::Release);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inclusive_range() {
+        // 模拟JAB的接口，结束位置的字符也包含在结果中
+        let text = "你好，世界".encode_utf16().collect::<Vec<_>>();
+        let get_range = |start: i32, end: i32| {
+            let range = text.get(start as usize..=end as usize)?;
+            Some(String::from_utf16_lossy(range))
+        };
+        assert_eq!(get_inclusive_range(0, 2, get_range), "你好");
+        assert_eq!(get_inclusive_range(3, 5, get_range), "世界");
+        assert_eq!(get_inclusive_range(2, 2, get_range), "");
+        assert_eq!(get_inclusive_range(4, 9, get_range), "");
+    }
+}
@@ -11,9 +11,17 @@
  * See the License for the specific language governing permissions and limitations under the License.
  */
 
+use std::path::Path;
 use win_wrap::{
-    common::{get_foreground_window, Result},
+    common::{close_handle, get_foreground_window, Result},
     msaa::object::AccessibleObject,
+    threading::{get_window_thread_process_id, open_process},
+};
+use windows::{
+    core::PWSTR,
+    Win32::System::Threading::{
+        QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+    },
 };
 
 pub(crate) trait AccessibleWindowExt {
@@ -32,3 +40,29 @@ impl AccessibleWindowExt for AccessibleObject {
         AccessibleObject::from_window(get_foreground_window())
     }
 }
+
+/**
+ * 获取前景窗口所属进程的名称（例如notepad.exe，统一转换成小写），如果获取失败则返回None。
+ * */
+pub(crate) fn get_foreground_process_name() -> Option<String> {
+    let (_, pid) = get_window_thread_process_id(get_foreground_window());
+    let handle = open_process(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+
+    let mut buf = [0u16; 1024];
+    let mut size = buf.len() as u32;
+    let res = unsafe {
+        QueryFullProcessImageNameW(
+            handle,
+            PROCESS_NAME_WIN32,
+            PWSTR(buf.as_mut_ptr()),
+            &mut size,
+        )
+    };
+    close_handle(handle);
+    res.ok()?;
+
+    let path = String::from_utf16_lossy(&buf[..size as usize]);
+    Path::new(&path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+}
@@ -15,7 +15,11 @@ use crate::{
     commander::keyboard::keys::Keys,
     configs::{
         items::{general::Lang, tts::TtsConfig},
-        operations::{apply_mouse_config, save_auto_check_update, save_lang, save_run_on_startup},
+        operations::{
+            apply_mouse_config, export_keymap, export_speech_dict, import_keymap,
+            import_speech_dict, save_auto_check_update, save_lang, save_run_on_startup,
+            save_speech_transcript,
+        },
         ConfigRoot,
     },
     context::{Context, ContextAccessor},
@@ -186,6 +190,22 @@ pub(crate) fn set_auto_check_update_cmd(context: Weak<Context>, toggle: bool) {
     });
 }
 
+/// 设置是否保存语音记录
+pub(crate) fn set_speech_transcript_cmd(context: Weak<Context>, toggle: bool) {
+    save_speech_transcript(context.clone(), toggle);
+
+    let msg = if toggle {
+        t!("command.msg_transcript_on").to_string()
+    } else {
+        t!("command.msg_transcript_off").to_string()
+    };
+
+    let ctx = context.clone();
+    context.get_work_runtime().spawn(async move {
+        ctx.get_performer().speak(&msg).await;
+    });
+}
+
 /// 设置语言
 pub(crate) fn set_lang_cmd(context: Weak<Context>, index: usize) {
     let lang = match index {
@@ -226,9 +246,8 @@ pub(crate) fn set_voice_cmd(context: Weak<Context>, engine: String, name: String
             voice: (info.engine, info.id),
             ..root.tts_config
         };
-        root.tts_config = config.clone();
+        root.tts_config = config;
         ctx.get_config_manager().set_config(&root);
-        tts.apply_config(&config.clone()).await;
         ctx.get_performer()
             .speak(&t!("command.tts_role", value = info.name))
             .await;
@@ -244,13 +263,11 @@ pub(crate) fn set_speed_cmd(context: Weak<Context>, index: usize) {
         speed,
         ..root.tts_config
     };
-    root.tts_config = config.clone();
+    root.tts_config = config;
     context.get_config_manager().set_config(&root);
 
-    let tts = context.get_performer().get_tts();
     let ctx = context.clone();
     context.get_work_runtime().spawn(async move {
-        tts.apply_config(&config.clone()).await;
         ctx.get_performer()
             .speak(&t!("command.tts_speed", value = speed))
             .await;
@@ -266,13 +283,11 @@ pub(crate) fn set_pitch_cmd(context: Weak<Context>, index: usize) {
         pitch,
         ..root.tts_config
     };
-    root.tts_config = config.clone();
+    root.tts_config = config;
     context.get_config_manager().set_config(&root);
 
-    let tts = context.get_performer().get_tts();
     let ctx = context.clone();
     context.get_work_runtime().spawn(async move {
-        tts.apply_config(&config.clone()).await;
         ctx.get_performer()
             .speak(&t!("command.tts_pitch", value = pitch))
             .await;
@@ -288,13 +303,11 @@ pub(crate) fn set_volume_cmd(context: Weak<Context>, index: usize) {
         volume,
         ..root.tts_config
     };
-    root.tts_config = config.clone();
+    root.tts_config = config;
     context.get_config_manager().set_config(&root);
 
-    let tts = context.get_performer().get_tts();
     let ctx = context.clone();
     context.get_work_runtime().spawn(async move {
-        tts.apply_config(&config.clone()).await;
         ctx.get_performer()
             .speak(&t!("command.tts_volume", value = volume))
             .await;
@@ -346,6 +359,78 @@ pub(crate) fn import_config_cmd(context: Weak<Context>, path: PathBuf) {
     );
 }
 
+/// 导出语音词典
+pub(crate) fn export_speech_dict_cmd(context: Weak<Context>, path: PathBuf) {
+    let msg = match export_speech_dict(context, &path) {
+        Ok(_) => t!("command.msg_export_success"),
+        Err(e) => {
+            error!("Can't export the speech dictionary. {}", e);
+            t!("command.msg_dict_failed")
+        }
+    };
+
+    message_box(None, &msg, &t!("command.msg_mind_title"), MB_OK);
+}
+
+/// 导入语音词典
+pub(crate) fn import_speech_dict_cmd(context: Weak<Context>, path: PathBuf) {
+    let msg = match import_speech_dict(context, &path) {
+        Ok(count) => t!("command.msg_import_dict_success", count = count),
+        Err(e) => {
+            error!("Can't import the speech dictionary. {}", e);
+            t!("command.msg_dict_failed")
+        }
+    };
+
+    message_box(None, &msg, &t!("command.msg_mind_title"), MB_OK);
+}
+
+/// 导出键位文件
+pub(crate) fn export_keymap_cmd(context: Weak<Context>, path: PathBuf) {
+    let msg = match export_keymap(context, &path) {
+        Ok(_) => t!("command.msg_export_success"),
+        Err(e) => {
+            error!("Can't export the keymap. {}", e);
+            t!("command.msg_keymap_failed")
+        }
+    };
+
+    message_box(None, &msg, &t!("command.msg_mind_title"), MB_OK);
+}
+
+/// 导入键位文件
+pub(crate) fn import_keymap_cmd(context: Weak<Context>, path: PathBuf) {
+    let msg = match import_keymap(context.clone(), &path) {
+        Ok(result) if result.conflicts.is_empty() => {
+            t!("command.msg_import_keymap_success", count = result.count)
+        }
+        Ok(result) => {
+            // 列出没有导入的功能和热键，让用户自己调整
+            let talent_provider = context.get_talent_provider();
+            let conflicts = result
+                .conflicts
+                .iter()
+                .map(|(id, key)| match talent_provider.get_talent_by_id(id) {
+                    Some(talent) => format!("{} ({})", talent.get_doc(), key),
+                    None => format!("{} ({})", id, key),
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            t!(
+                "command.msg_import_keymap_conflicts",
+                count = result.count,
+                conflicts = conflicts
+            )
+        }
+        Err(e) => {
+            error!("Can't import the keymap. {}", e);
+            t!("command.msg_keymap_failed")
+        }
+    };
+
+    message_box(None, &msg, &t!("command.msg_mind_title"), MB_OK);
+}
+
 /// 还原默认配置
 pub(crate) fn reset_config_cmd(context: Weak<Context>) {
     let msg_params = MessageParams {
@@ -373,13 +458,9 @@ fn reapply_config(context: Weak<Context>) {
         error!("关闭开机自动启动失败");
     }
 
+    // 语音的配置通过订阅配置的修改自动应用
     let ctx = context.clone();
-    let tts = context.get_performer().get_tts();
-    let tts_cfg = config.tts_config.clone();
     context.get_work_runtime().spawn(async move {
-        // 应用配置到TTS
-        tts.apply_config(&tts_cfg.clone()).await;
-
         // 重新显示设置界面，更新界面上的状态值
         ctx.get_gui_provider().show_settings_form();
 
@@ -74,6 +74,12 @@ impl SettingsForm {
         self.hotkeys_ui.btn_clear.set_enabled(false);
     }
 
+    // 重新读取热键配置，例如导入键位文件以后
+    pub(crate) fn reload_hotkeys(&self) {
+        self.init_data();
+        self.update_list();
+    }
+
     // 初始化列表表头
     fn init_list_cols(&self) {
         let col_data = [
@@ -125,12 +131,12 @@ impl SettingsForm {
 
             // 如果存在自定义热键，就仅显示自定义热键，否则显示默认热键
             let (keys_str, col) = match custom_talent {
-                Some(combo_key) => (combo_key.to_string(), 2),
+                Some(combo_key) => (combo_key.get_text(), 2),
                 None => (
                     talent
                         .get_combo_key()
                         .unwrap_or(ComboKey::default())
-                        .to_string(),
+                        .get_text(),
                     1,
                 ),
             };
@@ -221,10 +227,6 @@ impl SettingsForm {
 
             self.custom_combo_keys.borrow_mut().remove(id);
             save_hotkeys(context.clone(), self.custom_combo_keys.borrow().clone());
-
-            context
-                .get_talent_provider()
-                .update_custom_combo_key_map(context.clone());
         }
 
         self.init_data();
@@ -241,7 +243,7 @@ impl SettingsForm {
         }
         let combo_key = combo_key.unwrap();
 
-        let key_str = combo_key.to_string();
+        let key_str = combo_key.get_text();
         self.hotkeys_ui.tb_keys_info.set_text(&key_str);
 
         let Some(context) = self.context.get() else {
@@ -270,10 +272,6 @@ impl SettingsForm {
                 .borrow_mut()
                 .insert(id.clone(), combo_key);
             save_hotkeys(context.clone(), self.custom_combo_keys.borrow().clone());
-
-            context
-                .get_talent_provider()
-                .update_custom_combo_key_map(context.clone());
         }
 
         self.init_data();
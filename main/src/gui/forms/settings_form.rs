@@ -16,15 +16,19 @@ use crate::{
     commander::keyboard::{combo_keys::ComboKey, keys::Keys},
     configs::{
         items::{general::Lang, tts::TtsPropertyItem},
-        operations::{get_auto_check_update, get_lang, get_mouse_read_state, get_run_on_startup},
+        operations::{
+            get_auto_check_update, get_lang, get_mouse_read_state, get_run_on_startup,
+            get_speech_transcript,
+        },
     },
     context::{Context, ContextAccessor},
     gui::{
         command::{
-            add_desktop_shortcut_cmd, check_update_cmd, export_config_cmd,
-            get_desktop_shortcut_path, import_config_cmd, reset_config_cmd,
-            set_auto_check_update_cmd, set_auto_start_cmd, set_lang_cmd, set_mouse_read_cmd,
-            set_pitch_cmd, set_speed_cmd, set_voice_cmd, set_volume_cmd,
+            add_desktop_shortcut_cmd, check_update_cmd, export_config_cmd, export_keymap_cmd,
+            export_speech_dict_cmd, get_desktop_shortcut_path, import_config_cmd,
+            import_keymap_cmd, import_speech_dict_cmd, reset_config_cmd, set_auto_check_update_cmd,
+            set_auto_start_cmd, set_lang_cmd, set_mouse_read_cmd, set_pitch_cmd,
+            set_speech_transcript_cmd, set_speed_cmd, set_voice_cmd, set_volume_cmd,
         },
         forms::hotkeys::HotKeysUi,
         utils::set_hook_simple,
@@ -117,6 +121,7 @@ pub struct SettingsForm {
     (ck_run_on_startup, OnButtonClick): [SettingsForm::on_run_on_startup(SELF, CTRL)],
     (ck_auot_update, OnButtonClick): [SettingsForm::on_auto_check_update(SELF, CTRL)],
     (btn_check_update, OnButtonClick): [SettingsForm::on_check_update],
+    (ck_speech_transcript, OnButtonClick): [SettingsForm::on_speech_transcript(SELF, CTRL)],
     (cb_lang, OnComboxBoxSelection): [SettingsForm::on_lang_changed(SELF, CTRL)],
     (btn_close, OnButtonClick): [SettingsForm::on_save],
     (finish_program_hotkeys_notice, OnNotice): [SettingsForm::on_finish_program_hotkeys_hook],
@@ -160,6 +165,10 @@ pub struct SettingsForm {
     #[nwg_events(
     (btn_import, OnButtonClick): [SettingsForm::on_import],
     (btn_export, OnButtonClick): [SettingsForm::on_export],
+    (btn_import_dict, OnButtonClick): [SettingsForm::on_import_dict],
+    (btn_export_dict, OnButtonClick): [SettingsForm::on_export_dict],
+    (btn_import_keymap, OnButtonClick): [SettingsForm::on_import_keymap],
+    (btn_export_keymap, OnButtonClick): [SettingsForm::on_export_keymap],
     (btn_reset, OnButtonClick): [SettingsForm::on_reset],
     (btn_close, OnButtonClick): [SettingsForm::on_save],
     )]
@@ -263,7 +272,7 @@ impl SettingsForm {
         let keys: Guard<Arc<ComboKey>> = self.general_ui.program_hotkeys.load();
         let keys: Arc<ComboKey> = keys.clone();
 
-        let keys_str = format!("{keys}");
+        let keys_str = keys.get_text();
         let info = format!("您确定要将{keys_str}用作程序启动的热键吗？");
 
         let msg_params = MessageParams {
@@ -298,6 +307,11 @@ impl SettingsForm {
         set_auto_check_update_cmd(self.context.get().unwrap().clone(), toggle);
     }
 
+    fn on_speech_transcript(&self, ctrl: &GeneralUi) {
+        let toggle = ctrl.ck_speech_transcript.check_state() == CheckBoxState::Checked;
+        set_speech_transcript_cmd(self.context.get().unwrap().clone(), toggle);
+    }
+
     fn on_check_update(&self) {
         check_update_cmd(self.context.get().unwrap().clone(), false);
     }
@@ -358,6 +372,71 @@ impl SettingsForm {
         }
     }
 
+    fn on_import_dict(&self) {
+        if self.advanced_ui.import_dict_dialog.run(Some(&self.window)) {
+            let path = self
+                .advanced_ui
+                .import_dict_dialog
+                .get_selected_item()
+                .unwrap();
+            import_speech_dict_cmd(
+                self.context.get().unwrap().clone(),
+                PathBuf::from(path.as_os_str()),
+            );
+        }
+    }
+
+    fn on_export_dict(&self) {
+        if self.advanced_ui.export_dict_dialog.run(Some(&self.window)) {
+            let path = self
+                .advanced_ui
+                .export_dict_dialog
+                .get_selected_item()
+                .unwrap();
+            export_speech_dict_cmd(
+                self.context.get().unwrap().clone(),
+                PathBuf::from(path.as_os_str()),
+            );
+        }
+    }
+
+    fn on_import_keymap(&self) {
+        if self
+            .advanced_ui
+            .import_keymap_dialog
+            .run(Some(&self.window))
+        {
+            let path = self
+                .advanced_ui
+                .import_keymap_dialog
+                .get_selected_item()
+                .unwrap();
+            import_keymap_cmd(
+                self.context.get().unwrap().clone(),
+                PathBuf::from(path.as_os_str()),
+            );
+            self.reload_hotkeys();
+        }
+    }
+
+    fn on_export_keymap(&self) {
+        if self
+            .advanced_ui
+            .export_keymap_dialog
+            .run(Some(&self.window))
+        {
+            let path = self
+                .advanced_ui
+                .export_keymap_dialog
+                .get_selected_item()
+                .unwrap();
+            export_keymap_cmd(
+                self.context.get().unwrap().clone(),
+                PathBuf::from(path.as_os_str()),
+            );
+        }
+    }
+
     fn on_reset(&self) {
         reset_config_cmd(self.context.get().unwrap().clone());
     }
@@ -386,6 +465,13 @@ impl SettingsForm {
         };
         self.general_ui.ck_auot_update.set_check_state(state);
 
+        // 更新保存语音记录显示
+        let state = match get_speech_transcript(self.context.get().unwrap().clone()) {
+            true => CheckBoxState::Checked,
+            false => CheckBoxState::Unchecked,
+        };
+        self.general_ui.ck_speech_transcript.set_check_state(state);
+
         // 更新语言显示
         let lang = get_lang(self.context.get().unwrap().clone());
         let index = match lang {
@@ -416,19 +502,19 @@ impl SettingsForm {
                 .collect();
             all_voice.store(Arc::new(voices));
             let voiceinfo = tts.get_tts_prop_value(Some(TtsPropertyItem::Voice)).await;
-            if let TtsProperty::Voice(v) = voiceinfo {
+            if let Some(TtsProperty::Voice(v)) = voiceinfo {
                 voice.0.replace(format_voice_info(&v));
             }
             let voiceinfo = tts.get_tts_prop_value(Some(TtsPropertyItem::Speed)).await;
-            if let TtsProperty::Speed(v) = voiceinfo {
+            if let Some(TtsProperty::Speed(v)) = voiceinfo {
                 speed.store(v, Ordering::Release);
             }
             let voiceinfo = tts.get_tts_prop_value(Some(TtsPropertyItem::Pitch)).await;
-            if let TtsProperty::Pitch(v) = voiceinfo {
+            if let Some(TtsProperty::Pitch(v)) = voiceinfo {
                 pitch.store(v, Ordering::Release);
             }
             let voiceinfo = tts.get_tts_prop_value(Some(TtsPropertyItem::Volume)).await;
-            if let TtsProperty::Volume(v) = voiceinfo {
+            if let Some(TtsProperty::Volume(v)) = voiceinfo {
                 volume.store(v, Ordering::Release);
             }
 
@@ -508,6 +594,10 @@ pub struct GeneralUi {
     #[nwg_layout_item(layout: layout, col: 1, row: 4)]
     btn_check_update: Button,
 
+    #[nwg_control(text: & t ! ("settings.ck_speech_transcript"))]
+    #[nwg_layout_item(layout: layout, col: 1, row: 5)]
+    ck_speech_transcript: CheckBox,
+
     #[nwg_control(text: & t ! ("settings.lang"))]
     #[nwg_layout_item(layout: layout, col: 1, row: 6)]
     lb_lang: Label,
@@ -604,6 +694,18 @@ pub struct AdvancedUi {
     #[nwg_resource(title: t ! ("settings.import_title").to_string(), action: nwg::FileDialogAction::Open, filters: "Zip(*.zip)")]
     import_dialog: FileDialog,
 
+    #[nwg_resource(title: t ! ("settings.export_dict_title").to_string(), action: nwg::FileDialogAction::Save, filters: "Toml(*.toml)")]
+    export_dict_dialog: FileDialog,
+
+    #[nwg_resource(title: t ! ("settings.import_dict_title").to_string(), action: nwg::FileDialogAction::Open, filters: "Toml(*.toml)")]
+    import_dict_dialog: FileDialog,
+
+    #[nwg_resource(title: t ! ("settings.export_keymap_title").to_string(), action: nwg::FileDialogAction::Save, filters: "Toml(*.toml)")]
+    export_keymap_dialog: FileDialog,
+
+    #[nwg_resource(title: t ! ("settings.import_keymap_title").to_string(), action: nwg::FileDialogAction::Open, filters: "Toml(*.toml)")]
+    import_keymap_dialog: FileDialog,
+
     #[nwg_layout(max_size: [1200, 800], min_size: [650, 480], spacing: 20, max_column: Some(3), max_row: Some(10))]
     layout: GridLayout,
 
@@ -622,6 +724,22 @@ pub struct AdvancedUi {
     #[nwg_layout_item(layout: layout, col: 1, row: 3)]
     btn_reset: Button,
 
+    #[nwg_control(text: & t ! ("settings.btn_import_dict"))]
+    #[nwg_layout_item(layout: layout, col: 1, row: 4)]
+    btn_import_dict: Button,
+
+    #[nwg_control(text: & t ! ("settings.btn_export_dict"))]
+    #[nwg_layout_item(layout: layout, col: 1, row: 5)]
+    btn_export_dict: Button,
+
+    #[nwg_control(text: & t ! ("settings.btn_import_keymap"))]
+    #[nwg_layout_item(layout: layout, col: 1, row: 6)]
+    btn_import_keymap: Button,
+
+    #[nwg_control(text: & t ! ("settings.btn_export_keymap"))]
+    #[nwg_layout_item(layout: layout, col: 1, row: 7)]
+    btn_export_keymap: Button,
+
     #[nwg_control(text: & t ! ("settings.btn_close"))]
     #[nwg_layout_item(layout: layout2, col: 3, row: 9)]
     btn_close: Button,
@@ -12,6 +12,7 @@
  */
 
 use crate::{
+    configs::items::{hotkeys::HotKeysConfig, sound::SoundEvent},
     context::{Context, ContextAccessor},
     ext::window::AccessibleWindowExt,
     performer::sound::SoundArgument::Single,
@@ -21,6 +22,7 @@ use crate::{
 use a11y::{get_ia2_lib_path, setup_for_rigela};
 use log::{error, info};
 use rigela_utils::{
+    fs::{get_data_directory_error, get_rigela_program_directory},
     killer::wait_until_killed,
     library::{get_rigela_library_path, setup_library},
 };
@@ -73,7 +75,9 @@ impl Launcher {
         // 播放启动时的音效
         let ctx = self.context.clone();
         self.context.get_work_runtime().spawn(async move {
-            ctx.get_performer().play_sound(Single("launch.wav")).await;
+            ctx.get_performer()
+                .play_sound(Single(SoundEvent::Launch))
+                .await;
         });
 
         // 注册com组件库
@@ -109,6 +113,24 @@ impl Launcher {
             self.context.get_performer().speak(&(o, 0)).await;
         }
 
+        // 提示配置文件中的问题，每个问题的详细信息已经写入日志
+        let problems = self.context.get_config_manager().take_problems();
+        if !problems.is_empty() {
+            self.context
+                .get_performer()
+                .speak(&t!("configs.problems", value = problems.len()))
+                .await;
+        }
+
+        // 提示无法使用数据目录，这时数据保存在临时目录中
+        if get_data_directory_error().is_some() {
+            let dir = get_rigela_program_directory();
+            self.context
+                .get_performer()
+                .speak(&t!("configs.data_dir_error", value = dir.display()))
+                .await;
+        }
+
         // 启动事件监听
         self.context
             .get_event_core()
@@ -119,6 +141,14 @@ impl Launcher {
         self.context
             .get_talent_provider()
             .update_custom_combo_key_map(Arc::downgrade(&self.context));
+        // 热键配置改变后（包括切换应用程序的配置和外部修改配置文件）重新生成热键映射
+        let ctx = Arc::downgrade(&self.context);
+        self.context
+            .get_config_manager()
+            .subscribe(move |_: &HotKeysConfig| {
+                ctx.get_talent_provider()
+                    .update_custom_combo_key_map(ctx.clone())
+            });
     }
 
     //noinspection RsUnresolvedPath
@@ -133,7 +163,7 @@ impl Launcher {
         // 播放退出音效
         self.context
             .get_performer()
-            .play_sound(Single("exit.wav"))
+            .play_sound(Single(SoundEvent::Exit))
             .await;
 
         // 清理上下文
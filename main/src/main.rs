@@ -54,12 +54,15 @@ mod terminator;
 use crate::terminator::Terminator;
 use launcher::Launcher;
 use log::info;
-use rigela_utils::{killer::kill, logger::init_logger};
+use rigela_utils::{fs::init_data_directory, killer::kill, logger::init_logger};
 use std::sync::{Arc, Weak};
 use tokio::runtime::Builder;
 use win_wrap::threading::get_current_thread_id;
 
 fn main() {
+    // 解析数据目录，这会修改环境变量，所以要在创建任何线程之前完成
+    init_data_directory();
+
     // 初始化日志库
     init_logger(None);
 
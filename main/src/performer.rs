@@ -11,19 +11,31 @@
  * See the License for the specific language governing permissions and limitations under the License.
  */
 
+pub(crate) mod audio;
 pub(crate) mod cache;
+pub(crate) mod char_dict;
+pub(crate) mod history;
+pub(crate) mod say_all;
+pub(crate) mod scheduler;
 pub(crate) mod sound;
 mod text_processing;
 pub(crate) mod tts;
 
 use crate::{
-    context::Context,
+    configs::items::{speech_dict::SpeechDictConfig, tts::TtsConfig},
+    context::{Context, ContextAccessor},
     performer::{
+        audio::{create_audio_sink, PcmFormat},
         cache::Cache,
+        char_dict::CharDict,
+        history::History,
+        say_all::SayAll,
+        scheduler::{Scheduler, SpeechChannel, SpeechPriority},
         sound::{Sound, SoundArgument},
-        tts::{sapi5::Sapi5Engine, vvtts::VvttsEngine, Tts},
+        tts::{command::CommandEngine, sapi5::Sapi5Engine, vvtts::VvttsEngine, Tts},
     },
 };
+use parking_lot::Mutex;
 use std::{
     sync::{Arc, Weak},
     time::Duration,
@@ -39,8 +51,12 @@ pub(crate) trait Speakable {
 #[derive(Debug)]
 pub(crate) struct Performer {
     tts: OnceCell<Arc<Tts>>,
+    scheduler: OnceCell<Arc<Scheduler>>,
     cache: OnceCell<Arc<Cache>>,
+    char_dict: OnceCell<Arc<CharDict>>,
+    history: OnceCell<Arc<History>>,
     sound: Arc<Sound>,
+    say_all: Arc<SayAll>,
 }
 
 impl Performer {
@@ -48,8 +64,12 @@ impl Performer {
     pub(crate) fn new() -> Self {
         Self {
             tts: OnceCell::new().into(),
+            scheduler: OnceCell::new().into(),
             cache: OnceCell::new().into(),
+            char_dict: OnceCell::new().into(),
+            history: OnceCell::new().into(),
             sound: Sound::new().into(),
+            say_all: SayAll::default().into(),
         }
     }
 
@@ -60,16 +80,68 @@ impl Performer {
      */
     pub(crate) async fn apply(&self, context: Weak<Context>) {
         self.sound.apply(context.clone());
+        self.history
+            .set(Arc::new(History::new(context.clone())))
+            .unwrap_or(());
 
         let tts = Arc::new(Tts::new(context.clone()));
         self.tts.set(tts.clone()).unwrap_or(());
-        tts.put_default_engine(Sapi5Engine::new())
-            .await
-            .add_engine(VvttsEngine::new(context.clone()).await)
+        // 语音配置改变后（包括切换应用程序的配置和外部修改配置文件）重新应用到TTS，只切换了调节的属性时不需要应用
+        let tts2 = Arc::downgrade(&tts);
+        let ctx = context.clone();
+        let applied = Mutex::new(context.get_config_manager().get_config().tts_config);
+        context
+            .get_config_manager()
+            .subscribe(move |cfg: &TtsConfig| {
+                let Some(tts) = tts2.upgrade() else {
+                    return;
+                };
+                {
+                    let mut applied = applied.lock();
+                    let item_only = *cfg
+                        == TtsConfig {
+                            item: cfg.item.clone(),
+                            ..applied.clone()
+                        };
+                    *applied = cfg.clone();
+                    if item_only {
+                        return;
+                    }
+                }
+                let cfg = cfg.clone();
+                ctx.get_work_runtime()
+                    .spawn(async move { tts.apply_config(&cfg).await });
+            });
+        let tts2 = Arc::downgrade(&tts);
+        context
+            .get_config_manager()
+            .subscribe(move |_: &SpeechDictConfig| {
+                if let Some(tts) = tts2.upgrade() {
+                    tts.clear_speech_dict();
+                }
+            });
+        let scheduler = Arc::new(Scheduler::new(tts.clone()));
+        self.scheduler.set(scheduler.clone()).unwrap_or(());
+        tokio::spawn(scheduler.run());
+        let config = context.get_config_manager().get_config();
+        let output = config.general_config.audio_output;
+        let sink = create_audio_sink(&output, "Sapi5", Sapi5Engine::FORMAT);
+        tts.put_default_engine(Sapi5Engine::new(sink)).await;
+        let sink = create_audio_sink(&output, "Vvtts", VvttsEngine::FORMAT);
+        tts.add_engine(VvttsEngine::new(context.clone(), sink).await)
             .await;
+        // 配置文件中的外部命令语音引擎
+        for cfg in config.tts_config.command_engines {
+            let format = PcmFormat::new(cfg.sample_rate, cfg.channels);
+            let sink = create_audio_sink(&output, &cfg.name, format);
+            tts.add_engine(CommandEngine::new(cfg, sink)).await;
+        }
         self.cache
             .set(Arc::new(Cache::build(context.clone()).await))
             .unwrap_or(());
+        self.char_dict
+            .set(Arc::new(CharDict::build(context.clone()).await))
+            .unwrap_or(());
     }
 
     /// 获取表演者的TTS对象
@@ -85,12 +157,45 @@ impl Performer {
         None
     }
 
+    /// 获取表演者的字符字典
+    pub(crate) fn get_char_dict(&self) -> Option<Arc<CharDict>> {
+        self.char_dict.get().cloned()
+    }
+
+    /// 获取表演者的语音历史
+    pub(crate) fn get_history(&self) -> Option<Arc<History>> {
+        self.history.get().cloned()
+    }
+
+    /// 获取表演者的连续朗读对象
+    pub(crate) fn get_say_all(&self) -> Arc<SayAll> {
+        self.say_all.clone()
+    }
+
     /**
-     朗读文字，如果当前有朗读的任务，则进行排队。
+     朗读文字，这会打断当前正在朗读的语音。
      本方法会等待朗读完毕，如果朗读成功，则返回true；如果中途通过stop函数停止，或者朗读失败，则返回false。
      `speakable` 实现了Speakable特征的对象。
      */
     pub(crate) async fn speak<S: Speakable>(&self, speakable: &S) -> bool {
+        self.speak_with(speakable, SpeechPriority::Interrupt, SpeechChannel::General)
+            .await
+    }
+
+    //noinspection StructuralWrap
+    /**
+     按照指定的优先级和通道朗读文字，由语音调度器决定是打断、排队还是等待空闲时朗读。
+     本方法会等待朗读完毕，如果朗读成功，则返回true；如果被其他语音替换、打断，或者朗读失败，则返回false。
+     `speakable` 实现了Speakable特征的对象。
+     `priority` 语音的优先级。
+     `channel` 语音的来源通道。
+     */
+    pub(crate) async fn speak_with<S: Speakable>(
+        &self,
+        speakable: &S,
+        priority: SpeechPriority,
+        channel: SpeechChannel,
+    ) -> bool {
         let text = speakable.get_sentence();
         if text.is_empty() {
             return false;
@@ -100,18 +205,87 @@ impl Performer {
         if let Some(cache) = self.cache.get() {
             cache.update(text.clone());
         }
+        // 记录语音历史
+        if let Some(history) = self.history.get() {
+            history.push(text.clone(), channel);
+        }
 
-        let tts = loop {
-            if let Some(tts) = self.tts.get() {
-                break tts;
-            } else {
-                // 如果tts没有加载好，就继续等待
-                sleep(Duration::from_millis(100)).await;
-            }
-        };
+        self.get_scheduler()
+            .await
+            .submit(text, priority, channel)
+            .await
+    }
+
+    //noinspection StructuralWrap
+    /**
+     朗读浏览的内容（例如缓冲区和语音历史中的文字），这会打断当前正在朗读的语音，但不会更新缓冲区，也不会记录到语音历史中。
+     本方法会等待朗读完毕，如果朗读成功，则返回true；如果被其他语音打断，或者朗读失败，则返回false。
+     `speakable` 实现了Speakable特征的对象。
+     */
+    pub(crate) async fn speak_review<S: Speakable>(&self, speakable: &S) -> bool {
+        self.speak_review_with(speakable, SpeechPriority::Interrupt, SpeechChannel::Talent)
+            .await
+    }
+
+    //noinspection StructuralWrap
+    /**
+     按照指定的优先级和通道朗读浏览的内容，和speak_review一样不会更新缓冲区和语音历史，连续朗读使用这个方法，避免整篇文档挤掉真正的语音历史。
+     本方法会等待朗读完毕，返回值和speak_with相同。
+     `speakable` 实现了Speakable特征的对象。
+     `priority` 语音的优先级。
+     `channel` 语音的来源通道。
+     */
+    pub(crate) async fn speak_review_with<S: Speakable>(
+        &self,
+        speakable: &S,
+        priority: SpeechPriority,
+        channel: SpeechChannel,
+    ) -> bool {
+        let text = speakable.get_sentence();
+        if text.is_empty() {
+            return false;
+        }
+
+        self.get_scheduler()
+            .await
+            .submit(text, priority, channel)
+            .await
+    }
 
-        tts.stop().await;
-        return tts.speak(text).await;
+    //noinspection StructuralWrap
+    /**
+     朗读浏览内容的SSML，和speak_review一样不会更新缓冲区和语音历史。
+     本方法会等待朗读完毕，如果朗读成功，则返回true；如果被其他语音打断，或者格式不正确，则返回false。
+     `ssml` SSML文本。
+     */
+    pub(crate) async fn speak_review_ssml(&self, ssml: &str) -> bool {
+        self.get_scheduler()
+            .await
+            .submit_ssml(
+                ssml.to_string(),
+                SpeechPriority::Interrupt,
+                SpeechChannel::Talent,
+            )
+            .await
+    }
+
+    /**
+     停止朗读，等待朗读的语音也会被丢弃。所有的语音都经过语音调度器朗读，所以只需要停止调度器，调度器会停止全部的语音引擎。
+     */
+    pub(crate) async fn stop(&self) {
+        if let Some(scheduler) = self.scheduler.get() {
+            scheduler.stop();
+        }
+    }
+
+    // 获取语音调度器，如果tts没有加载好，就继续等待
+    async fn get_scheduler(&self) -> &Arc<Scheduler> {
+        loop {
+            if let Some(scheduler) = self.scheduler.get() {
+                break scheduler;
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
     }
 
     /// 播放音效
@@ -0,0 +1,351 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::configs::items::general::AudioOutput;
+use log::error;
+use parking_lot::Mutex;
+use rigela_utils::{
+    bass::BassChannelOutputStream,
+    wav::{PcmBuffer, WavWriter},
+};
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+pub(crate) use rigela_utils::wav::PcmFormat;
+
+//noinspection StructuralWrap
+/**
+ * 音频的输出端，语音引擎和音效播放器把音频写入到这里，而不需要关心音频最终输出到声卡、文件还是内存。
+ * */
+#[async_trait::async_trait]
+pub(crate) trait AudioSink: Debug + Send + Sync {
+    /// 开始或继续输出，音频文件会从头开始播放
+    fn start(&self);
+
+    /**
+     * 写入PCM数据。
+     * `data` 音频数据。
+     * `format` 音频的格式。
+     * */
+    fn put_pcm(&self, data: &[u8], format: PcmFormat);
+
+    /// 停止输出，还没有输出的数据会被丢弃
+    fn stop(&self);
+
+    /// 是否已经停止输出
+    fn is_stopped(&self) -> bool;
+
+    /// 等待直到停止或者所有的数据都已经输出
+    async fn wait(&self);
+
+    /**
+     * 设置播放频率。
+     * `value` 要播放的频率。
+     * */
+    fn set_freq(&self, value: f32);
+
+    /**
+     * 设置声道平衡。
+     * `value` 平衡，-1是左声道，0是居中，1是右声道。
+     * */
+    fn set_pan(&self, value: f32);
+
+    /**
+     * 设置音量。
+     * `value` 音量，0是静音，1是原始音量。
+     * */
+    fn set_volume(&self, value: f32);
+}
+
+/**
+ * 按照音频的输出方式创建语音引擎使用的输出端。
+ * `output` 音频的输出方式。
+ * `name` 语音引擎的名称，写入文件时用于区分不同的引擎。
+ * `format` 语音引擎输出的音频格式。
+ * */
+pub(crate) fn create_audio_sink(
+    output: &AudioOutput,
+    name: &str,
+    format: PcmFormat,
+) -> Arc<dyn AudioSink> {
+    match output {
+        AudioOutput::Device => Arc::new(BassSink::new(format)),
+        AudioOutput::WavFile(path) => {
+            let path = Path::new(path);
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let path = path.with_file_name(format!("{}-{}.wav", stem, name));
+            Arc::new(WavFileSink::new(path))
+        }
+        AudioOutput::Null => Arc::new(MemorySink::default()),
+    }
+}
+
+/**
+ * 按照音频的输出方式创建播放音频文件的输出端，如果无法打开文件，则返回None。
+ * 只有通过声卡播放时音效才会发声。
+ * `output` 音频的输出方式。
+ * `path` 音频文件的路径。
+ * */
+pub(crate) fn create_file_sink(output: &AudioOutput, path: &str) -> Option<Arc<dyn AudioSink>> {
+    match output {
+        AudioOutput::Device => BassSink::from_file(path).map(|s| Arc::new(s) as Arc<dyn AudioSink>),
+        _ => Some(Arc::new(MemorySink::default())),
+    }
+}
+
+// 设置过的输出流参数，重新创建输出流以后需要再次应用
+#[derive(Debug, Default)]
+struct StreamParams {
+    freq: Option<f32>,
+    pan: Option<f32>,
+    volume: Option<f32>,
+}
+
+/// 通过bass库输出到声卡
+#[derive(Debug)]
+pub(crate) struct BassSink {
+    // 当前输出流的格式，播放音频文件时是None
+    stream: Mutex<(Option<PcmFormat>, Arc<BassChannelOutputStream>)>,
+    params: Mutex<StreamParams>,
+}
+
+impl BassSink {
+    /**
+     * 创建PCM数据的输出端。
+     * `format` 音频的格式，写入不同格式的数据时会重新创建输出流。
+     * */
+    pub(crate) fn new(format: PcmFormat) -> Self {
+        let stream = BassChannelOutputStream::new(format.sample_rate, format.channels);
+        Self {
+            stream: (Some(format), Arc::new(stream)).into(),
+            params: Default::default(),
+        }
+    }
+
+    /**
+     * 创建播放音频文件的输出端，如果无法打开文件，则返回None。
+     * `path` 音频文件的路径。
+     * */
+    pub(crate) fn from_file(path: &str) -> Option<Self> {
+        let stream = BassChannelOutputStream::from_disk_file(path);
+        if !stream.is_valid() {
+            return None;
+        }
+        Some(Self {
+            stream: (None, Arc::new(stream)).into(),
+            params: Default::default(),
+        })
+    }
+
+    fn get_stream(&self) -> Arc<BassChannelOutputStream> {
+        self.stream.lock().1.clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl AudioSink for BassSink {
+    fn start(&self) {
+        let lock = self.stream.lock();
+        match lock.0 {
+            Some(_) => lock.1.start(),
+            None => lock.1.play(true),
+        }
+    }
+
+    fn put_pcm(&self, data: &[u8], format: PcmFormat) {
+        let mut lock = self.stream.lock();
+        match lock.0 {
+            // 音频文件不能写入数据
+            None => return,
+            Some(f) if f != format => {
+                let stream = BassChannelOutputStream::new(format.sample_rate, format.channels);
+                let params = self.params.lock();
+                if let Some(v) = params.freq {
+                    stream.set_freq(v);
+                }
+                if let Some(v) = params.pan {
+                    stream.set_pan(v);
+                }
+                if let Some(v) = params.volume {
+                    stream.set_volume(v);
+                }
+                lock.1.stop();
+                stream.start();
+                *lock = (Some(format), Arc::new(stream));
+            }
+            _ => {}
+        }
+        lock.1.put_data(data);
+    }
+
+    fn stop(&self) {
+        self.get_stream().stop();
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.get_stream().is_stopped()
+    }
+
+    async fn wait(&self) {
+        // 等待时不能持有锁，否则其他线程无法停止输出
+        let stream = self.get_stream();
+        stream.wait_until_stopped_or_stalled().await;
+    }
+
+    fn set_freq(&self, value: f32) {
+        self.params.lock().freq = Some(value);
+        self.get_stream().set_freq(value);
+    }
+
+    fn set_pan(&self, value: f32) {
+        self.params.lock().pan = Some(value);
+        self.get_stream().set_pan(value);
+    }
+
+    fn set_volume(&self, value: f32) {
+        self.params.lock().volume = Some(value);
+        self.get_stream().set_volume(value);
+    }
+}
+
+//noinspection StructuralWrap
+/**
+ * 把音频写入WAV文件，文件在第一次写入数据时创建，此后写入的数据都追加到文件中。
+ * 文件只能使用一种格式，和第一次写入的格式不同的数据会被丢弃。
+ * */
+#[derive(Debug)]
+pub(crate) struct WavFileSink {
+    writer: Mutex<WavWriter>,
+    stopped: AtomicBool,
+}
+
+impl WavFileSink {
+    /**
+     * 创建WAV文件的输出端。
+     * `path` 文件的路径，已经存在的文件会被覆盖。
+     * */
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self {
+            writer: WavWriter::new(path).into(),
+            stopped: AtomicBool::new(false),
+        }
+    }
+
+    // 把缓冲的数据和文件头中的长度写入文件
+    fn finish(&self) {
+        let mut writer = self.writer.lock();
+        if let Err(e) = writer.finish() {
+            error!(
+                "Can't write the wav file ({}). {}",
+                writer.get_path().display(),
+                e
+            );
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AudioSink for WavFileSink {
+    fn start(&self) {
+        self.stopped.store(false, Ordering::Release);
+    }
+
+    fn put_pcm(&self, data: &[u8], format: PcmFormat) {
+        if self.is_stopped() {
+            return;
+        }
+        let mut writer = self.writer.lock();
+        if let Err(e) = writer.write(data, format) {
+            error!(
+                "Can't write the wav file ({}). {}",
+                writer.get_path().display(),
+                e
+            );
+        }
+    }
+
+    fn stop(&self) {
+        self.stopped.store(true, Ordering::Release);
+        self.finish();
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Acquire)
+    }
+
+    async fn wait(&self) {
+        // 每段语音输出完毕以后写入完整的文件头
+        self.finish();
+    }
+
+    fn set_freq(&self, _value: f32) {}
+
+    fn set_pan(&self, _value: f32) {}
+
+    fn set_volume(&self, _value: f32) {}
+}
+
+//noinspection StructuralWrap
+/**
+ * 把音频保存在内存中，不发出任何声音。每次开始输出时会清空之前的数据，所以只保留最后一段音频。
+ * */
+#[derive(Debug, Default)]
+pub(crate) struct MemorySink {
+    data: Mutex<PcmBuffer>,
+    stopped: AtomicBool,
+}
+
+#[async_trait::async_trait]
+impl AudioSink for MemorySink {
+    fn start(&self) {
+        self.data.lock().clear();
+        self.stopped.store(false, Ordering::Release);
+    }
+
+    fn put_pcm(&self, data: &[u8], format: PcmFormat) {
+        if self.is_stopped() {
+            return;
+        }
+        self.data.lock().append(data, format);
+    }
+
+    fn stop(&self) {
+        self.stopped.store(true, Ordering::Release);
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Acquire)
+    }
+
+    async fn wait(&self) {}
+
+    fn set_freq(&self, _value: f32) {}
+
+    fn set_pan(&self, _value: f32) {}
+
+    fn set_volume(&self, _value: f32) {}
+}
+
+#[cfg(test)]
+impl MemorySink {
+    // 获取最后一段音频的格式和数据
+    pub(crate) fn get_data(&self) -> (Option<PcmFormat>, Vec<u8>) {
+        self.data.lock().get_data()
+    }
+}
@@ -11,12 +11,15 @@
  * See the License for the specific language governing permissions and limitations under the License.
  */
 
-use crate::context::{Context, ContextAccessor};
+use crate::{
+    context::{Context, ContextAccessor},
+    performer::text_processing::units::{split_lines, split_sentences, split_words},
+};
 use arc_swap::ArcSwapAny;
 use log::error;
 use rust_i18n::AtomicStr;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt::Debug,
     sync::{
         atomic::{AtomicI64, Ordering},
@@ -31,6 +34,7 @@ pub(crate) struct Cache {
     char_list: ArcSwapAny<Arc<Vec<char>>>,
     index: AtomicI64,
     word_map: Arc<HashMap<String, String>>,
+    lexicon: Arc<HashSet<String>>,
 }
 
 impl Cache {
@@ -55,11 +59,21 @@ impl Cache {
             Err(_) => Default::default(),
         };
 
+        // 从组词数据中收集中文词语，作为按词浏览时的词库
+        let lexicon = word_map
+            .iter()
+            .flat_map(|(k, v)| [k, v])
+            .flat_map(|s| s.split(|c: char| !c.is_alphabetic() || c.is_ascii()))
+            .filter(|w| (2..=4).contains(&w.chars().count()))
+            .map(|w| w.to_string())
+            .collect();
+
         Self {
             data: AtomicStr::new(""),
             char_list: Default::default(),
             index: AtomicI64::new(-1),
             word_map,
+            lexicon: Arc::new(lexicon),
         }
     }
 
@@ -93,6 +107,49 @@ impl Cache {
         }
     }
 
+    //noinspection StructuralWrap
+    /**
+     * 按照指定的单位获取内容，参数可以是上一个，下一个，或者当前。
+     * 浏览位置和按字符浏览（get方法）共用，已经到达开头或结尾时停留在原位置；缓冲区为空时返回空字符串。
+     * `unit` 浏览的单位。
+     * `direction` 访问方向。
+     * */
+    pub(crate) fn get_unit(&self, unit: CacheUnit, direction: Direction) -> String {
+        if self.data.to_string().is_empty() {
+            return String::new();
+        }
+
+        let index = self.index.load(Ordering::Acquire);
+        if index == -1 {
+            self.get_first_char();
+        }
+        let list = self.char_list.load();
+        let ranges = match unit {
+            CacheUnit::Word => split_words(&list, &self.lexicon),
+            CacheUnit::Sentence => split_sentences(&list),
+            CacheUnit::Line => split_lines(&list),
+        };
+        if ranges.is_empty() {
+            return String::new();
+        }
+
+        let current = ranges
+            .iter()
+            .rposition(|r| r.start as i64 <= index)
+            .unwrap_or(0);
+        let target = match direction {
+            // 第一次浏览时从第一个单位开始
+            _ if index == -1 => 0,
+            Direction::Forward => (current + 1).min(ranges.len() - 1),
+            Direction::Backward => current.saturating_sub(1),
+            Direction::Current => current,
+        };
+
+        let range = ranges[target].clone();
+        self.index.store(range.start as i64, Ordering::Release);
+        list[range].iter().collect()
+    }
+
     /// 获取缓冲区数据
     pub(crate) fn get_data(&self) -> String {
         self.data.to_string().clone()
@@ -132,6 +189,14 @@ pub(crate) enum Direction {
     Backward,
 }
 
+/// 缓冲区浏览的单位
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum CacheUnit {
+    Word,
+    Sentence,
+    Line,
+}
+
 impl Debug for Cache {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Cache").finish()
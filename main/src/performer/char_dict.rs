@@ -0,0 +1,247 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::{
+    context::{Context, ContextAccessor},
+    performer::text_processing::symbols::get_symbol,
+};
+use log::error;
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Formatter},
+    sync::Weak,
+};
+use tokio::io::AsyncReadExt;
+
+/// 字典的资源文件名称
+const RESOURCE_NAME: &str = "chars.txt";
+
+/// 字符的一条解释
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CharDefinition {
+    /// 文字解释，例如读音、部件拆分或名称
+    Text(String),
+    /// 字符的码位，需要逐个字符朗读
+    CodePoint(u32),
+}
+
+/// 一个字符的字典条目
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct CharEntry {
+    /// 读音（拼音），多音字有多个读音
+    pub(crate) readings: Vec<String>,
+    /// 部件拆分，例如“木子李”
+    pub(crate) components: String,
+    /// 示例词语
+    pub(crate) words: Vec<String>,
+    /// 非汉字字符的名称，为空时使用Unicode名称
+    pub(crate) name: String,
+}
+
+//noinspection StructuralWrap
+/**
+ * 字符字典，可以查询字符的读音、部件拆分、示例词语和Unicode名称。
+ * 资源文件每行一个字符，字段之间使用“|”分隔，依次是：字符|读音|部件拆分|示例词语|Unicode名称，其中多个读音或词语使用“,”分隔，例如：
+ * 李|lǐ|木子李|李子,行李,桃李|
+ * é||||LATIN SMALL LETTER E WITH ACUTE
+ * */
+pub(crate) struct CharDict {
+    entries: HashMap<char, CharEntry>,
+    // 上一次查询的字符和解释的序号，连续查询同一个字符时依次朗读每一条解释
+    last: Mutex<Option<(char, usize)>>,
+}
+
+impl CharDict {
+    //noinspection DuplicatedCode
+    /// 创建字符字典，从资源文件加载数据
+    pub(crate) async fn build(context: Weak<Context>) -> Self {
+        let entries = match context.get_resource_provider().open(RESOURCE_NAME).await {
+            Ok(mut f) => {
+                let mut data: String = Default::default();
+                if f.read_to_string(&mut data).await.is_err() {
+                    error!("load chars data error")
+                }
+                Self::parse(&data)
+            }
+            Err(e) => {
+                error!("Can't open the character dictionary. {}", e);
+                Default::default()
+            }
+        };
+
+        Self::from_entries(entries)
+    }
+
+    fn from_entries(entries: HashMap<char, CharEntry>) -> Self {
+        Self {
+            entries,
+            last: None.into(),
+        }
+    }
+
+    // 解析字典数据，格式不正确的行会被跳过
+    fn parse(data: &str) -> HashMap<char, CharEntry> {
+        let split_list = |s: Option<&str>| -> Vec<String> {
+            s.unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|i| !i.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+
+        data.lines()
+            .filter_map(|line| {
+                let mut fields = line.split('|');
+                let mut chars = fields.next()?.chars();
+                let (Some(ch), None) = (chars.next(), chars.next()) else {
+                    return None;
+                };
+                let entry = CharEntry {
+                    readings: split_list(fields.next()),
+                    components: fields.next().unwrap_or_default().trim().to_string(),
+                    words: split_list(fields.next()),
+                    name: fields.next().unwrap_or_default().trim().to_string(),
+                };
+                Some((ch, entry))
+            })
+            .collect()
+    }
+
+    //noinspection StructuralWrap
+    /**
+     * 获取字符的所有解释，依次是读音、部件拆分、示例词语、符号名称、Unicode名称和码位，字典中没有的内容会被跳过。
+     * `ch` 需要查询的字符。
+     * */
+    pub(crate) fn get_definitions(&self, ch: char) -> Vec<CharDefinition> {
+        let mut result = vec![];
+        let entry = self.entries.get(&ch).cloned().unwrap_or_default();
+
+        if !entry.readings.is_empty() {
+            result.push(entry.readings.join("，"));
+        }
+        if !entry.components.is_empty() {
+            result.push(entry.components);
+        }
+        if !entry.words.is_empty() {
+            result.push(entry.words.join("，"));
+        }
+        if result.is_empty() {
+            if let Some((name, _)) = get_symbol(&ch) {
+                result.push(name.to_string());
+            }
+        }
+        if !entry.name.is_empty() {
+            result.push(entry.name);
+        } else if let Some(name) = get_unicode_name(ch) {
+            result.push(name);
+        }
+
+        let mut result: Vec<_> = result.into_iter().map(CharDefinition::Text).collect();
+        result.push(CharDefinition::CodePoint(ch as u32));
+        result
+    }
+
+    /**
+     * 查询字符，连续查询同一个字符时依次返回下一条解释，全部返回后从头开始。
+     * `ch` 需要查询的字符。
+     * */
+    pub(crate) fn lookup(&self, ch: char) -> CharDefinition {
+        let definitions = self.get_definitions(ch);
+        let mut last = self.last.lock();
+        let index = match *last {
+            Some((c, i)) if c == ch => (i + 1) % definitions.len(),
+            _ => 0,
+        };
+        *last = Some((ch, index));
+
+        definitions[index].clone()
+    }
+}
+
+// 获取字符的Unicode名称，汉字的名称只是重复码位，所以不返回
+fn get_unicode_name(ch: char) -> Option<String> {
+    let name = unicode_names2::name(ch)?.to_string();
+    (!name.contains("IDEOGRAPH-")).then_some(name)
+}
+
+impl Debug for CharDict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CharDict")
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: &str = "李|lǐ|木子李|李子,行李,桃李|\n\
+        行|xíng,háng|双人行|银行|\n\
+        é||||LATIN SMALL LETTER E WITH ACUTE\n\
+        错误的行\n";
+
+    #[test]
+    fn test_parse() {
+        let entries = CharDict::parse(DATA);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[&'行'].readings, vec!["xíng", "háng"]);
+        assert_eq!(entries[&'李'].components, "木子李");
+    }
+
+    #[test]
+    fn test_lookup_steps() {
+        let dict = CharDict::from_entries(CharDict::parse(DATA));
+        let steps: Vec<CharDefinition> = (0..5).map(|_| dict.lookup('李')).collect();
+        assert_eq!(
+            steps,
+            vec![
+                CharDefinition::Text("lǐ".to_string()),
+                CharDefinition::Text("木子李".to_string()),
+                CharDefinition::Text("李子，行李，桃李".to_string()),
+                CharDefinition::CodePoint(0x674E),
+                CharDefinition::Text("lǐ".to_string()),
+            ]
+        );
+        assert_eq!(
+            dict.lookup('é'),
+            CharDefinition::Text("LATIN SMALL LETTER E WITH ACUTE".to_string())
+        );
+    }
+
+    #[test]
+    fn test_unicode_name_fallback() {
+        let dict = CharDict::from_entries(Default::default());
+        assert_eq!(
+            dict.get_definitions('='),
+            vec![
+                CharDefinition::Text("等于".to_string()),
+                CharDefinition::Text("EQUALS SIGN".to_string()),
+                CharDefinition::CodePoint(0x3D),
+            ]
+        );
+        assert_eq!(
+            dict.get_definitions('Ω'),
+            vec![
+                CharDefinition::Text("GREEK CAPITAL LETTER OMEGA".to_string()),
+                CharDefinition::CodePoint(0x3A9),
+            ]
+        );
+        assert_eq!(
+            dict.get_definitions('中'),
+            vec![CharDefinition::CodePoint(0x4E2D)]
+        );
+    }
+}
@@ -0,0 +1,216 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::{
+    context::{Context, ContextAccessor},
+    performer::scheduler::SpeechChannel,
+};
+use chrono::{DateTime, Local};
+use log::error;
+use parking_lot::Mutex;
+use rigela_utils::fs::get_rigela_program_directory;
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Formatter},
+    fs::{create_dir_all, File, OpenOptions},
+    io::Write,
+    sync::Weak,
+};
+
+/// 语音历史最多保存的条目数量
+const HISTORY_CAPACITY: usize = 200;
+
+/// 一条语音历史
+#[derive(Debug, Clone)]
+pub(crate) struct HistoryEntry {
+    /// 朗读的文字
+    pub(crate) text: String,
+    /// 朗读的时间
+    pub(crate) time: DateTime<Local>,
+    /// 语音的来源
+    pub(crate) channel: SpeechChannel,
+}
+
+/// 语音历史，保存最近朗读过的语音，并支持逐条回顾
+pub(crate) struct History {
+    context: Weak<Context>,
+    entries: Mutex<VecDeque<HistoryEntry>>,
+    // 当前回顾的位置，每次记录新的语音后回到最新的一条
+    position: Mutex<usize>,
+    // 本次会话的语音记录文件，第一次写入时创建
+    transcript: Mutex<Option<File>>,
+    started: DateTime<Local>,
+}
+
+impl History {
+    /**
+     * 创建语音历史。
+     * `context` 读屏框架的上下文环境。
+     * */
+    pub(crate) fn new(context: Weak<Context>) -> Self {
+        Self {
+            context,
+            entries: VecDeque::with_capacity(HISTORY_CAPACITY).into(),
+            position: 0.into(),
+            transcript: None.into(),
+            started: Local::now(),
+        }
+    }
+
+    /**
+     * 记录一条语音，如果开启了保存语音记录，同时写入到日志文件夹中的记录文件。
+     * `text` 朗读的文字。
+     * `channel` 语音的来源通道。
+     * */
+    pub(crate) fn push(&self, text: String, channel: SpeechChannel) {
+        let entry = HistoryEntry {
+            text,
+            time: Local::now(),
+            channel,
+        };
+
+        let config = self.context.get_config_manager().get_config();
+        if config.general_config.save_speech_transcript {
+            self.write_transcript(&entry);
+        }
+
+        self.record(entry);
+    }
+
+    // 把语音放入历史，超出容量时丢弃最早的一条
+    fn record(&self, entry: HistoryEntry) {
+        let mut entries = self.entries.lock();
+        if entries.len() >= HISTORY_CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+        *self.position.lock() = entries.len() - 1;
+    }
+
+    /**
+     * 获取当前回顾位置的语音。
+     * */
+    pub(crate) fn current(&self) -> Option<HistoryEntry> {
+        let entries = self.entries.lock();
+        let position = self.position.lock();
+        entries.get(*position).cloned()
+    }
+
+    /**
+     * 回顾上一条（更早的）语音，如果已经是最早的一条，则返回None。
+     * */
+    pub(crate) fn prev(&self) -> Option<HistoryEntry> {
+        let entries = self.entries.lock();
+        let mut position = self.position.lock();
+        if *position == 0 {
+            return None;
+        }
+        *position -= 1;
+        entries.get(*position).cloned()
+    }
+
+    /**
+     * 回顾下一条（更新的）语音，如果已经是最新的一条，则返回None。
+     * */
+    pub(crate) fn next(&self) -> Option<HistoryEntry> {
+        let entries = self.entries.lock();
+        let mut position = self.position.lock();
+        if *position + 1 >= entries.len() {
+            return None;
+        }
+        *position += 1;
+        entries.get(*position).cloned()
+    }
+
+    // 追加一行到本次会话的语音记录文件
+    fn write_transcript(&self, entry: &HistoryEntry) {
+        let mut transcript = self.transcript.lock();
+        if transcript.is_none() {
+            let dir = get_rigela_program_directory().join("logs");
+            let path = dir.join(format!(
+                "transcript-{}.txt",
+                self.started.format("%Y%m%d-%H%M%S")
+            ));
+            let file = create_dir_all(&dir)
+                .and_then(|_| OpenOptions::new().create(true).append(true).open(&path));
+            match file {
+                Ok(f) => *transcript = Some(f),
+                Err(e) => {
+                    error!("Can't open the transcript file {}. {}", path.display(), e);
+                    return;
+                }
+            }
+        }
+
+        if let Some(file) = transcript.as_mut() {
+            let line = format!(
+                "{} [{:?}] {}\n",
+                entry.time.format("%H:%M:%S"),
+                entry.channel,
+                entry.text
+            );
+            if let Err(e) = file.write_all(line.as_bytes()) {
+                error!("Can't write the transcript. {}", e);
+            }
+        }
+    }
+}
+
+impl Debug for History {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("History")
+            .field("entries", &self.entries.lock().len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(text: &str) -> HistoryEntry {
+        HistoryEntry {
+            text: text.to_string(),
+            time: Local::now(),
+            channel: SpeechChannel::General,
+        }
+    }
+
+    #[test]
+    fn test_review() {
+        let history = History::new(Weak::new());
+        assert!(history.current().is_none());
+        ["a", "b", "c"]
+            .iter()
+            .for_each(|t| history.record(entry(t)));
+
+        assert_eq!(history.current().unwrap().text, "c");
+        assert!(history.next().is_none());
+        assert_eq!(history.prev().unwrap().text, "b");
+        assert_eq!(history.prev().unwrap().text, "a");
+        assert!(history.prev().is_none());
+        assert_eq!(history.next().unwrap().text, "b");
+
+        // 新的语音让回顾位置回到最新的一条
+        history.record(entry("d"));
+        assert_eq!(history.current().unwrap().text, "d");
+    }
+
+    #[test]
+    fn test_capacity() {
+        let history = History::new(Weak::new());
+        (0..HISTORY_CAPACITY + 5).for_each(|i| history.record(entry(&i.to_string())));
+        assert_eq!(history.entries.lock().len(), HISTORY_CAPACITY);
+        assert_eq!(history.entries.lock().front().unwrap().text, "5");
+    }
+}
@@ -0,0 +1,335 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::{
+    context::{Context, ContextAccessor},
+    performer::{
+        scheduler::{SpeechChannel, SpeechPriority},
+        text_processing::units::split_sentences,
+    },
+};
+use log::info;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Weak,
+    },
+};
+
+/// 每次从文本来源获取的文字长度（UTF-16编码单元）
+const BLOCK_SIZE: usize = 2000;
+
+//noinspection StructuralWrap
+/**
+ * 连续朗读的文本来源，例如编辑框或者文档。
+ * 所有的位置都使用UTF-16编码单元计算，这和IA2、JAB以及UIA中的偏移量是一致的。
+ * */
+pub(crate) trait TextSource: Send + Sync {
+    /**
+     * 获取文字的总长度，不能预先知道长度的文本来源可以返回一个上限，读到结尾以后get_text返回的文字会比要求的短。
+     * */
+    fn get_length(&self) -> usize;
+
+    /**
+     * 获取一段文字。
+     * `start` 开始位置。
+     * `end` 结束位置（不包括）。
+     * */
+    fn get_text(&self, start: usize, end: usize) -> String;
+
+    /**
+     * 获取插入点的位置。
+     * */
+    fn get_caret(&self) -> usize;
+
+    /**
+     * 移动插入点，成功返回true。
+     * `offset` 插入点的新位置。
+     * */
+    fn set_caret(&self, offset: usize) -> bool;
+}
+
+/// 一个句子和他在文本来源中的范围
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Sentence {
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) text: String,
+}
+
+//noinspection StructuralWrap
+/**
+ * 句子阅读器，从文本来源中分块获取文字，切分成句子后依次返回，并记录当前朗读到的位置。
+ * 阅读器和具体的无障碍接口无关，只依赖TextSource。
+ * */
+pub(crate) struct SentenceReader<'a> {
+    source: &'a dyn TextSource,
+    length: usize,
+    // 下一次获取文字的开始位置
+    fetch_pos: usize,
+    pending: VecDeque<Sentence>,
+    current: Option<Sentence>,
+}
+
+impl<'a> SentenceReader<'a> {
+    /**
+     * 创建句子阅读器。
+     * `source` 文本来源。
+     * `start` 开始朗读的位置。
+     * */
+    pub(crate) fn new(source: &'a dyn TextSource, start: usize) -> Self {
+        let length = source.get_length();
+        Self {
+            source,
+            length,
+            fetch_pos: start.min(length),
+            pending: VecDeque::new(),
+            current: None,
+        }
+    }
+
+    /**
+     * 获取下一个句子，已经读到结尾时返回None。
+     * */
+    pub(crate) fn next_sentence(&mut self) -> Option<Sentence> {
+        while self.pending.is_empty() && self.fetch_pos < self.length {
+            self.fetch();
+        }
+        self.current = self.pending.pop_front();
+        self.current.clone()
+    }
+
+    /**
+     * 获取最后一次返回的句子，也就是当前正在朗读的句子。
+     * */
+    pub(crate) fn get_current(&self) -> Option<&Sentence> {
+        self.current.as_ref()
+    }
+
+    // 获取一块文字并切分成句子
+    fn fetch(&mut self) {
+        let start = self.fetch_pos;
+        let end = (start + BLOCK_SIZE).min(self.length);
+        let text = self.source.get_text(start, end);
+        let chars = text.chars().collect::<Vec<_>>();
+
+        // 每个字符在文本来源中的位置
+        let mut offsets = Vec::with_capacity(chars.len() + 1);
+        let mut pos = start;
+        for ch in chars.iter() {
+            offsets.push(pos);
+            pos += ch.len_utf16();
+        }
+        offsets.push(pos);
+
+        let mut ranges = split_sentences(&chars);
+        // 没有到达结尾时，最后一个句子可能被截断了，留到下一次再获取
+        if end < self.length && ranges.len() > 1 {
+            let last = ranges.pop().unwrap();
+            self.fetch_pos = offsets[last.start];
+        } else if chars.is_empty() {
+            // 文本来源返回的文字比预期的短，剩下的部分不再读取
+            self.fetch_pos = self.length;
+        } else {
+            self.fetch_pos = end;
+        }
+
+        for range in ranges {
+            self.pending.push_back(Sentence {
+                start: offsets[range.start],
+                end: offsets[range.end],
+                text: chars[range].iter().collect(),
+            });
+        }
+    }
+}
+
+//noinspection StructuralWrap
+/**
+ * 连续朗读（全文朗读），从插入点开始一句一句地朗读文本来源中的文字，按下任意键时停止。
+ * */
+#[derive(Debug, Default)]
+pub(crate) struct SayAll {
+    // 每次开始或停止朗读时加一，用于让旧的朗读任务退出
+    generation: AtomicU64,
+    running: AtomicBool,
+}
+
+impl SayAll {
+    //noinspection StructuralWrap
+    /**
+     * 从插入点开始连续朗读，本方法会等待朗读结束或者被停止。
+     * `context` 读屏框架的上下文环境。
+     * `source` 文本来源。
+     * `move_caret` 朗读结束后是否把插入点移动到最后朗读的位置。
+     * */
+    pub(crate) async fn start(
+        &self,
+        context: Weak<Context>,
+        source: Box<dyn TextSource>,
+        move_caret: bool,
+    ) {
+        let id = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.running.store(true, Ordering::Release);
+
+        let performer = context.get_performer();
+        let mut reader = SentenceReader::new(source.as_ref(), source.get_caret());
+        let mut priority = SpeechPriority::Interrupt;
+        let mut finished = true;
+
+        while let Some(sentence) = reader.next_sentence() {
+            // 文档的内容不记录到语音历史和缓冲区中
+            let spoken = performer
+                .speak_review_with(&sentence.text, priority, SpeechChannel::General)
+                .await;
+            if !spoken || self.generation.load(Ordering::Acquire) != id {
+                finished = false;
+                break;
+            }
+            // 后面的句子排在前一句之后，不打断其他的语音
+            priority = SpeechPriority::Queue;
+        }
+
+        if move_caret {
+            // 被打断时停在当前句子的开头，读完时停在最后一句的结尾
+            let offset = match (reader.get_current(), finished) {
+                (Some(s), true) => Some(s.end),
+                (Some(s), false) => Some(s.start),
+                (None, _) => None,
+            };
+            if let Some(offset) = offset {
+                if !source.set_caret(offset) {
+                    info!("Can't move the caret to {offset} after saying all.");
+                }
+            }
+        }
+
+        if self.generation.load(Ordering::Acquire) == id {
+            self.running.store(false, Ordering::Release);
+        }
+    }
+
+    /**
+     * 停止连续朗读，如果确实正在朗读则返回true，调用者需要负责停止语音。
+     * */
+    pub(crate) fn stop(&self) -> bool {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.running.swap(false, Ordering::AcqRel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct StringSource(Vec<u16>, Mutex<usize>);
+
+    impl StringSource {
+        fn new(text: &str, caret: usize) -> Self {
+            Self(text.encode_utf16().collect(), Mutex::new(caret))
+        }
+    }
+
+    impl TextSource for StringSource {
+        fn get_length(&self) -> usize {
+            self.0.len()
+        }
+
+        fn get_text(&self, start: usize, end: usize) -> String {
+            String::from_utf16_lossy(&self.0[start..end])
+        }
+
+        fn get_caret(&self) -> usize {
+            *self.1.lock().unwrap()
+        }
+
+        fn set_caret(&self, offset: usize) -> bool {
+            *self.1.lock().unwrap() = offset;
+            true
+        }
+    }
+
+    // 不能预先知道长度的文本来源，例如UIA
+    struct UnknownLengthSource(StringSource);
+
+    impl TextSource for UnknownLengthSource {
+        fn get_length(&self) -> usize {
+            usize::MAX
+        }
+
+        fn get_text(&self, start: usize, end: usize) -> String {
+            let length = self.0.get_length();
+            self.0.get_text(start.min(length), end.min(length))
+        }
+
+        fn get_caret(&self) -> usize {
+            self.0.get_caret()
+        }
+
+        fn set_caret(&self, offset: usize) -> bool {
+            self.0.set_caret(offset)
+        }
+    }
+
+    fn read_all(source: &dyn TextSource, start: usize) -> Vec<Sentence> {
+        let mut reader = SentenceReader::new(source, start);
+        let mut result = vec![];
+        while let Some(s) = reader.next_sentence() {
+            result.push(s);
+        }
+        result
+    }
+
+    #[test]
+    fn test_read_sentences() {
+        let source = StringSource::new("你好。Hello world! 😀再见\n", 0);
+        let result = read_all(&source, 0);
+        let texts = result.iter().map(|s| s.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["你好。", "Hello world!", "😀再见"]);
+        // 位置使用UTF-16编码单元计算，表情符号占两个单元
+        assert_eq!((result[2].start, result[2].end), (16, 20));
+        assert_eq!(source.get_text(result[2].start, result[2].end), "😀再见");
+
+        // 从中间开始朗读
+        let result = read_all(&source, 3);
+        assert_eq!(result[0].text, "Hello world!");
+        assert_eq!(result[0].start, 3);
+    }
+
+    #[test]
+    fn test_read_blocks() {
+        // 句子跨越了分块的边界，不能被截断
+        let sentence = "这是一个比较长的句子。";
+        let text = sentence.repeat(BLOCK_SIZE / 7);
+        let source = StringSource::new(&text, 0);
+        let result = read_all(&source, 0);
+        assert_eq!(result.len(), BLOCK_SIZE / 7);
+        assert!(result.iter().all(|s| s.text == sentence));
+        assert_eq!(result.last().unwrap().end, source.get_length());
+
+        assert!(read_all(&StringSource::new(" \n ", 0), 0).is_empty());
+    }
+
+    #[test]
+    fn test_read_unknown_length() {
+        let sentence = "这是一个比较长的句子。";
+        let text = sentence.repeat(BLOCK_SIZE / 7);
+        let source = UnknownLengthSource(StringSource::new(&text, 0));
+        let result = read_all(&source, 0);
+        assert_eq!(result.len(), BLOCK_SIZE / 7);
+        assert!(result.iter().all(|s| s.text == sentence));
+        assert_eq!(result.last().unwrap().end, text.encode_utf16().count());
+    }
+}
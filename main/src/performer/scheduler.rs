@@ -0,0 +1,422 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::performer::tts::Tts;
+use parking_lot::Mutex;
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Formatter},
+    future::Future,
+    sync::Arc,
+};
+use tokio::sync::{oneshot, Notify};
+
+/// 语音的优先级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum SpeechPriority {
+    /// 停止当前的朗读并清空队列，立即朗读
+    Interrupt,
+    /// 排在当前朗读之后，先于追加和后台的语音
+    Queue,
+    /// 追加到队列末尾，等前面的语音都朗读完毕再朗读
+    Append,
+    /// 后台语音，任何其他优先级的语音都可以打断他
+    Background,
+}
+
+/// 语音的来源通道，同一个通道中新的语音会替换还没有开始朗读的旧语音
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum SpeechChannel {
+    General,
+    Focus,
+    Caret,
+    Input,
+    Ime,
+    Dialog,
+    Talent,
+}
+
+/// 语音调度器的输出接口，调度器通过他把文字交给语音引擎
+#[async_trait::async_trait]
+pub(crate) trait SpeechOutput {
+    /**
+    朗读文字并等待朗读完毕，如果朗读成功，则返回true；如果中途被停止，则返回false。
+    `text` 需要朗读的文本。
+    */
+    async fn speak(&self, text: String) -> bool;
+
+    /**
+    朗读SSML并等待朗读完毕，如果朗读成功，则返回true；如果中途被停止，或者格式不正确，则返回false。
+    `ssml` SSML文本。
+    */
+    async fn speak_ssml(&self, ssml: String) -> bool;
+
+    /// 清除停止的状态，调度器在取出下一条语音时调用，此后的stop会停止这条语音。
+    fn reset(&self);
+
+    /// 停止当前的朗读。
+    fn stop(&self);
+}
+
+#[async_trait::async_trait]
+impl SpeechOutput for Tts {
+    async fn speak(&self, text: String) -> bool {
+        Tts::speak(self, text).await
+    }
+
+    async fn speak_ssml(&self, ssml: String) -> bool {
+        Tts::speak_ssml(self, &ssml).await
+    }
+
+    fn reset(&self) {
+        Tts::reset(self)
+    }
+
+    fn stop(&self) {
+        // 语音标记可以切换到任何引擎朗读，所以需要停止所有的引擎
+        Tts::stop_all(self)
+    }
+}
+
+// 等待朗读的语音
+struct PendingUtterance {
+    text: String,
+    // text是否是SSML
+    ssml: bool,
+    priority: SpeechPriority,
+    channel: SpeechChannel,
+    done: oneshot::Sender<bool>,
+}
+
+// 调度器的状态，取出语音和记录当前的语音必须在同一个锁中完成，否则打断的语音可能既看不到等待的语音，也看不到正在朗读的语音
+#[derive(Default)]
+struct SchedulerState {
+    pending: VecDeque<PendingUtterance>,
+    current: Option<(SpeechPriority, SpeechChannel)>,
+}
+
+impl SchedulerState {
+    // 丢弃所有等待朗读的语音
+    fn drain(&mut self) {
+        self.pending
+            .drain(..)
+            .for_each(|i| i.done.send(false).unwrap_or(()));
+    }
+}
+
+/// 语音调度器，按照优先级和通道安排每一条语音的朗读顺序
+pub(crate) struct Scheduler {
+    output: Arc<dyn SpeechOutput + Send + Sync>,
+    state: Mutex<SchedulerState>,
+    notify: Notify,
+}
+
+impl Scheduler {
+    /**
+    创建语音调度器。
+    `output` 语音的输出对象。
+    */
+    pub(crate) fn new(output: Arc<dyn SpeechOutput + Send + Sync>) -> Self {
+        Self {
+            output,
+            state: Default::default(),
+            notify: Notify::new(),
+        }
+    }
+
+    /**
+    运行调度循环，依次朗读队列中的语音，此方法不会返回，需要放在单独的任务中运行。
+    */
+    pub(crate) async fn run(self: Arc<Self>) {
+        loop {
+            let next = {
+                let mut state = self.state.lock();
+                let next = state.pending.pop_front();
+                if let Some(item) = next.as_ref() {
+                    state.current = Some((item.priority, item.channel));
+                    // 在锁中清除停止的状态，之后的打断和停止一定能看到这条语音
+                    self.output.reset();
+                }
+                next
+            };
+            let Some(item) = next else {
+                self.notify.notified().await;
+                continue;
+            };
+
+            let result = match item.ssml {
+                true => self.output.speak_ssml(item.text).await,
+                false => self.output.speak(item.text).await,
+            };
+            self.state.lock().current = None;
+            item.done.send(result).unwrap_or(());
+        }
+    }
+
+    //noinspection StructuralWrap
+    /**
+    提交一条语音，语音在调用时就会入队，返回的Future等待他朗读完毕。
+    如果朗读成功，则返回true；如果被其他语音替换、打断，或者朗读失败，则返回false。
+    `text` 需要朗读的文本。
+    `priority` 语音的优先级。
+    `channel` 语音的来源通道。
+    */
+    pub(crate) fn submit(
+        &self,
+        text: String,
+        priority: SpeechPriority,
+        channel: SpeechChannel,
+    ) -> impl Future<Output = bool> {
+        let done = self.enqueue(text, false, priority, channel);
+        async move { done.await.unwrap_or(false) }
+    }
+
+    //noinspection StructuralWrap
+    /**
+    提交一条SSML语音，返回值和submit相同。
+    `ssml` 需要朗读的SSML文本。
+    `priority` 语音的优先级。
+    `channel` 语音的来源通道。
+    */
+    pub(crate) fn submit_ssml(
+        &self,
+        ssml: String,
+        priority: SpeechPriority,
+        channel: SpeechChannel,
+    ) -> impl Future<Output = bool> {
+        let done = self.enqueue(ssml, true, priority, channel);
+        async move { done.await.unwrap_or(false) }
+    }
+
+    /**
+    停止当前的朗读，并丢弃所有等待朗读的语音。
+    */
+    pub(crate) fn stop(&self) {
+        let mut state = self.state.lock();
+        state.drain();
+        self.output.stop();
+    }
+
+    fn enqueue(
+        &self,
+        text: String,
+        ssml: bool,
+        priority: SpeechPriority,
+        channel: SpeechChannel,
+    ) -> oneshot::Receiver<bool> {
+        let (tx, rx) = oneshot::channel();
+        let item = PendingUtterance {
+            text,
+            ssml,
+            priority,
+            channel,
+            done: tx,
+        };
+
+        let mut state = self.state.lock();
+        let should_stop = match priority {
+            SpeechPriority::Interrupt => {
+                state.drain();
+                state.current.is_some()
+            }
+            SpeechPriority::Background => false,
+            _ => matches!(state.current, Some((SpeechPriority::Background, _))),
+        };
+        if should_stop {
+            // 先停止再入队，新的语音在释放锁以后才能被取出，所以不会被这次停止误伤
+            self.output.stop();
+        }
+
+        let pending = &mut state.pending;
+        if priority != SpeechPriority::Append {
+            // 同一个通道中还没有朗读的语音已经过时，丢弃他们（追加的语音需要保留）
+            let mut i = 0;
+            while i < pending.len() {
+                if pending[i].channel == channel && pending[i].priority != SpeechPriority::Append {
+                    if let Some(old) = pending.remove(i) {
+                        old.done.send(false).unwrap_or(());
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+        }
+        let index = pending
+            .iter()
+            .position(|i| i.priority > priority)
+            .unwrap_or(pending.len());
+        pending.insert(index, item);
+        drop(state);
+        self.notify.notify_one();
+
+        rx
+    }
+}
+
+impl Debug for Scheduler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("pending", &self.state.lock().pending.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::{mpsc, watch};
+
+    // 记录所有朗读内容的模拟引擎，开始朗读时发出通知，直到测试允许结束或者被停止才返回
+    struct GatedOutput {
+        spoken: Mutex<Vec<String>>,
+        started: mpsc::UnboundedSender<String>,
+        finish: Notify,
+        stopped: watch::Sender<bool>,
+    }
+
+    #[async_trait::async_trait]
+    impl SpeechOutput for GatedOutput {
+        async fn speak(&self, text: String) -> bool {
+            self.spoken.lock().push(text.clone());
+            self.started.send(text).unwrap_or(());
+            let mut stopped = self.stopped.subscribe();
+            tokio::select! {
+                _ = self.finish.notified() => true,
+                _ = stopped.wait_for(|s| *s) => false,
+            }
+        }
+
+        async fn speak_ssml(&self, ssml: String) -> bool {
+            self.speak(ssml).await
+        }
+
+        fn reset(&self) {
+            self.stopped.send_replace(false);
+        }
+
+        fn stop(&self) {
+            self.stopped.send_replace(true);
+        }
+    }
+
+    fn make_scheduler() -> (
+        Arc<Scheduler>,
+        Arc<GatedOutput>,
+        mpsc::UnboundedReceiver<String>,
+    ) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let output = Arc::new(GatedOutput {
+            spoken: vec![].into(),
+            started: tx,
+            finish: Notify::new(),
+            stopped: watch::Sender::new(false),
+        });
+        let scheduler = Arc::new(Scheduler::new(output.clone()));
+        tokio::spawn(scheduler.clone().run());
+        (scheduler, output, rx)
+    }
+
+    #[tokio::test]
+    async fn test_queue_after_current() {
+        let (scheduler, output, mut started) = make_scheduler();
+        let a = scheduler.submit("a".into(), SpeechPriority::Interrupt, SpeechChannel::Ime);
+        let a = tokio::spawn(a);
+        assert_eq!(started.recv().await.unwrap(), "a");
+        let b =
+            tokio::spawn(scheduler.submit("b".into(), SpeechPriority::Queue, SpeechChannel::Ime));
+        output.finish.notify_one();
+        assert_eq!(started.recv().await.unwrap(), "b");
+        output.finish.notify_one();
+        assert!(a.await.unwrap() && b.await.unwrap());
+        assert_eq!(*output.spoken.lock(), vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_interrupt() {
+        let (scheduler, output, mut started) = make_scheduler();
+        let a = tokio::spawn(scheduler.submit(
+            "a".into(),
+            SpeechPriority::Interrupt,
+            SpeechChannel::Focus,
+        ));
+        assert_eq!(started.recv().await.unwrap(), "a");
+        let b = scheduler.submit("b".into(), SpeechPriority::Append, SpeechChannel::Talent);
+        let c = scheduler.submit("c".into(), SpeechPriority::Interrupt, SpeechChannel::Focus);
+        assert_eq!(started.recv().await.unwrap(), "c");
+        output.finish.notify_one();
+        assert!(!a.await.unwrap() && !b.await && c.await);
+        assert_eq!(*output.spoken.lock(), vec!["a", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_channel_replaces_pending() {
+        let (scheduler, output, mut started) = make_scheduler();
+        let a = tokio::spawn(scheduler.submit(
+            "a".into(),
+            SpeechPriority::Interrupt,
+            SpeechChannel::Talent,
+        ));
+        assert_eq!(started.recv().await.unwrap(), "a");
+        let b = scheduler.submit("b".into(), SpeechPriority::Queue, SpeechChannel::Caret);
+        let c =
+            tokio::spawn(scheduler.submit("c".into(), SpeechPriority::Queue, SpeechChannel::Caret));
+        assert!(!b.await);
+        output.finish.notify_one();
+        assert_eq!(started.recv().await.unwrap(), "c");
+        output.finish.notify_one();
+        assert!(a.await.unwrap() && c.await.unwrap());
+        assert_eq!(*output.spoken.lock(), vec!["a", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_stop_drains_queue() {
+        let (scheduler, output, mut started) = make_scheduler();
+        let a = scheduler.submit("a".into(), SpeechPriority::Interrupt, SpeechChannel::Focus);
+        let a = tokio::spawn(a);
+        assert_eq!(started.recv().await.unwrap(), "a");
+        let b = scheduler.submit("b".into(), SpeechPriority::Append, SpeechChannel::Talent);
+        scheduler.stop();
+        assert!(!a.await.unwrap() && !b.await);
+
+        // 停止以后提交的语音正常朗读
+        let c = tokio::spawn(scheduler.submit(
+            "c".into(),
+            SpeechPriority::Queue,
+            SpeechChannel::Talent,
+        ));
+        assert_eq!(started.recv().await.unwrap(), "c");
+        output.finish.notify_one();
+        assert!(c.await.unwrap());
+        assert_eq!(*output.spoken.lock(), vec!["a", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_background_is_preempted() {
+        let (scheduler, output, mut started) = make_scheduler();
+        let a = tokio::spawn(scheduler.submit(
+            "a".into(),
+            SpeechPriority::Background,
+            SpeechChannel::General,
+        ));
+        assert_eq!(started.recv().await.unwrap(), "a");
+        let b = tokio::spawn(scheduler.submit(
+            "b".into(),
+            SpeechPriority::Append,
+            SpeechChannel::Talent,
+        ));
+        assert_eq!(started.recv().await.unwrap(), "b");
+        output.finish.notify_one();
+        assert!(!a.await.unwrap() && b.await.unwrap());
+        assert_eq!(*output.spoken.lock(), vec!["a", "b"]);
+    }
+}
@@ -11,8 +11,13 @@
  * See the License for the specific language governing permissions and limitations under the License.
  */
 
-use crate::context::{Context, ContextAccessor};
-use rigela_utils::bass::BassChannelOutputStream;
+use crate::{
+    configs::items::{general::AudioOutput, sound::SoundEvent},
+    context::{Context, ContextAccessor},
+    performer::audio::{create_file_sink, AudioSink},
+};
+use log::error;
+use rigela_utils::fs::get_rigela_program_directory;
 use std::{
     collections::HashMap,
     sync::{Arc, OnceLock, Weak},
@@ -20,17 +25,20 @@ use std::{
 };
 use tokio::{sync::Mutex, time::sleep};
 
+/// 创建音效输出流失败时的重试次数
+const CREATE_RETRY_TIMES: usize = 10;
+
 /// 声音参数
 pub(crate) enum SoundArgument {
-    Single(&'static str),
-    WithFreq(&'static str, f32),
+    Single(SoundEvent),
+    WithFreq(SoundEvent, f32),
 }
 
 /// 音效播放器
 #[derive(Debug)]
 pub(crate) struct Sound {
     context: OnceLock<Weak<Context>>,
-    sound_streams: Mutex<HashMap<String, Arc<BassChannelOutputStream>>>,
+    sound_streams: Mutex<HashMap<String, Arc<dyn AudioSink>>>,
 }
 
 impl Sound {
@@ -57,44 +65,92 @@ impl Sound {
             }
             sleep(Duration::from_millis(100)).await;
         };
-        let res_name = match arg {
-            SoundArgument::Single(n) => n,
-            SoundArgument::WithFreq(n, _) => n,
+        let event = match arg {
+            SoundArgument::Single(e) => e,
+            SoundArgument::WithFreq(e, _) => e,
         };
-        let lock = self.sound_streams.lock().await;
-        let stream = match lock.get(res_name) {
-            None => {
-                drop(lock);
-                context
-                    .get_resource_provider()
-                    .open(&res_name)
-                    .await
-                    .unwrap();
-                loop {
-                    let s = BassChannelOutputStream::from_disk_file(
-                        context.get_resource_provider().get_path(&res_name).as_str(),
+        let root = context.get_config_manager().get_config();
+        let config = root.sound_config;
+        let item = config.get_item(event);
+        if !item.enabled {
+            return;
+        }
+
+        // 优先使用音效包中的文件，音效包中没有的使用内置的音效
+        let pack_file = config.scheme.map(|scheme| {
+            get_rigela_program_directory()
+                .join("sounds")
+                .join(scheme)
+                .join(&item.file)
+        });
+        let output = &root.general_config.audio_output;
+        let stream = match pack_file.filter(|p| p.exists()) {
+            Some(p) => match self.get_or_create(&p.to_string_lossy(), output).await {
+                Some(s) => Some(s),
+                None => {
+                    error!(
+                        "Can't play the sound `{}`, use the built-in sound instead.",
+                        p.display()
                     );
-                    if s.is_valid() {
-                        let mut lock = self.sound_streams.lock().await;
-                        lock.insert(res_name.to_string(), s.into());
-                        break;
-                    }
-                    sleep(Duration::from_millis(100)).await;
+                    self.get_builtin(context, &item.file, output).await
                 }
-                let lock = self.sound_streams.lock().await;
-                lock.get(res_name).unwrap().clone()
-            }
-            Some(s) => {
-                let s = s.clone();
-                drop(lock);
-                s
-            }
+            },
+            None => self.get_builtin(context, &item.file, output).await,
         };
+        let Some(stream) = stream else {
+            return;
+        };
+
+        stream.set_volume(item.volume.clamp(0, 100) as f32 / 100.0);
+        stream.set_pan(item.pan.clamp(-100, 100) as f32 / 100.0);
         if let SoundArgument::WithFreq(_, f) = arg {
             stream.set_freq(f);
         }
-        stream.play(true);
-        stream.wait_until_stopped_or_stalled().await;
+        stream.start();
+        stream.wait().await;
+    }
+
+    // 获取内置音效的输出流，只在没有缓存的时候打开资源文件
+    async fn get_builtin(
+        &self,
+        context: &Weak<Context>,
+        file: &str,
+        output: &AudioOutput,
+    ) -> Option<Arc<dyn AudioSink>> {
+        let path = context.get_resource_provider().get_path(file);
+        if let Some(s) = self.sound_streams.lock().await.get(&path) {
+            return Some(s.clone());
+        }
+
+        if let Err(e) = context.get_resource_provider().open(file).await {
+            error!("Can't open the sound `{}`. {}", file, e);
+            return None;
+        }
+        let stream = self.get_or_create(&path, output).await;
+        if stream.is_none() {
+            error!("Can't play the sound `{}`.", file);
+        }
+
+        stream
+    }
+
+    // 获取音效文件的输出流，没有缓存的时候创建并放入缓存
+    // 文件可能还没有写入完毕，所以创建失败时会重试几次，仍然失败则返回None
+    async fn get_or_create(&self, path: &str, output: &AudioOutput) -> Option<Arc<dyn AudioSink>> {
+        if let Some(s) = self.sound_streams.lock().await.get(path) {
+            return Some(s.clone());
+        }
+
+        for _ in 0..CREATE_RETRY_TIMES {
+            if let Some(s) = create_file_sink(output, path) {
+                let mut lock = self.sound_streams.lock().await;
+                lock.insert(path.to_string(), s.clone());
+                return Some(s);
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+
+        None
     }
 
     /**
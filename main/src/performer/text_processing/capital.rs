@@ -0,0 +1,184 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::{
+    configs::items::tts::CapitalConfig,
+    performer::text_processing::ssml::{MarkupNode, Prosody, ProsodyValue},
+};
+
+/// 判断文字是否只有一个大写字母
+pub(crate) fn is_single_capital(text: &str) -> bool {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => ch.is_uppercase(),
+        _ => false,
+    }
+}
+
+//noinspection StructuralWrap
+/**
+ * 把逐字朗读的文字转换成语音标记的节点，其中的每一个大写字母都按照设置加上提示（提高音调或者在前面朗读提示文字）。
+ * `text` 逐字朗读的文字。
+ * `config` 大写字母的提示方式。
+ * `pitch` 当前的音调，提高音调时以此为基准。
+ * `prefix` 朗读在大写字母前面的提示文字。
+ * */
+pub(crate) fn mark_capitals(
+    text: &str,
+    config: &CapitalConfig,
+    pitch: i32,
+    prefix: &str,
+) -> Vec<MarkupNode> {
+    let mut result = vec![];
+    let mut run = String::new();
+
+    for ch in text.chars() {
+        if !ch.is_uppercase() {
+            run.push(ch);
+            continue;
+        }
+
+        if !run.is_empty() {
+            result.push(MarkupNode::Characters(run.clone()));
+            run.clear();
+        }
+        if config.say_prefix {
+            result.push(MarkupNode::Text(format!(" {} ", prefix)));
+        }
+        // 大写字母前后加上空格，避免和相邻的字母连在一起朗读
+        let letter = MarkupNode::Text(format!(" {} ", ch));
+        if config.pitch_change == 0 {
+            result.push(letter);
+        } else {
+            let prosody = Prosody {
+                pitch: Some(ProsodyValue::Absolute(pitch + config.pitch_change)),
+                ..Default::default()
+            };
+            result.push(MarkupNode::Prosody(prosody, vec![letter]));
+        }
+    }
+    if !run.is_empty() {
+        result.push(MarkupNode::Characters(run));
+    }
+
+    result
+}
+
+//noinspection StructuralWrap
+/**
+ * 给语音标记中所有逐字朗读的文字加上大写字母的提示，其他节点保持不变。
+ * `nodes` 语音标记的节点。
+ * `config` 大写字母的提示方式。
+ * `pitch` 当前的音调。
+ * `prefix` 朗读在大写字母前面的提示文字。
+ * */
+pub(crate) fn mark_spelled_capitals(
+    nodes: &[MarkupNode],
+    config: &CapitalConfig,
+    pitch: i32,
+    prefix: &str,
+) -> Vec<MarkupNode> {
+    let mut result = vec![];
+    for node in nodes {
+        match node {
+            MarkupNode::Characters(t) => result.extend(mark_capitals(t, config, pitch, prefix)),
+            MarkupNode::Prosody(p, children) => result.push(MarkupNode::Prosody(
+                p.clone(),
+                mark_spelled_capitals(children, config, pitch, prefix),
+            )),
+            MarkupNode::Voice(v, children) => result.push(MarkupNode::Voice(
+                v.clone(),
+                mark_spelled_capitals(children, config, pitch, prefix),
+            )),
+            _ => result.push(node.clone()),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(pitch_change: i32, say_prefix: bool) -> CapitalConfig {
+        CapitalConfig {
+            pitch_change,
+            say_prefix,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_is_single_capital() {
+        assert!(is_single_capital("A"));
+        assert!(is_single_capital("Ж"));
+        assert!(!is_single_capital("a"));
+        assert!(!is_single_capital("AB"));
+        assert!(!is_single_capital("中"));
+        assert!(!is_single_capital(""));
+    }
+
+    #[test]
+    fn test_mark_capitals() {
+        let raised = Prosody {
+            pitch: Some(ProsodyValue::Absolute(70)),
+            ..Default::default()
+        };
+        assert_eq!(
+            mark_capitals("A", &config(20, false), 50, "cap"),
+            vec![MarkupNode::Prosody(
+                raised.clone(),
+                vec![MarkupNode::Text(" A ".to_string())]
+            )]
+        );
+        assert_eq!(
+            mark_capitals("iPad", &config(0, true), 50, "cap"),
+            vec![
+                MarkupNode::Characters("i".to_string()),
+                MarkupNode::Text(" cap ".to_string()),
+                MarkupNode::Text(" P ".to_string()),
+                MarkupNode::Characters("ad".to_string()),
+            ]
+        );
+        assert_eq!(
+            mark_capitals("abc", &config(20, true), 50, "cap"),
+            vec![MarkupNode::Characters("abc".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_mark_spelled_capitals() {
+        let nodes = vec![
+            MarkupNode::Text("OK".to_string()),
+            MarkupNode::Voice(
+                "voice".to_string(),
+                vec![MarkupNode::Characters("Ok".to_string())],
+            ),
+        ];
+        assert_eq!(
+            mark_spelled_capitals(&nodes, &config(0, true), 50, "cap"),
+            vec![
+                MarkupNode::Text("OK".to_string()),
+                MarkupNode::Voice(
+                    "voice".to_string(),
+                    vec![
+                        MarkupNode::Text(" cap ".to_string()),
+                        MarkupNode::Text(" O ".to_string()),
+                        MarkupNode::Characters("k".to_string()),
+                    ]
+                ),
+            ]
+        );
+    }
+}
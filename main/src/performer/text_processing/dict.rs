@@ -0,0 +1,218 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::configs::items::speech_dict::{DictEntry, DictEntryKind};
+use log::error;
+use regex::{NoExpand, Regex, RegexBuilder};
+
+// 编译好的词典规则
+#[derive(Debug)]
+struct DictRule {
+    regex: Regex,
+    replacement: String,
+    expand: bool,
+    // 是否只替换完整的单词，边界在匹配以后检查，因为正则表达式的\b不适用于非单词字符开头或结尾的条目
+    whole_word: bool,
+    app: Option<String>,
+}
+
+/**
+ * 语音词典，在文字送入语音引擎之前替换其中的读音。
+ * 规则的应用顺序是固定的：先应用指定了应用程序的条目，再应用全局条目，同一类条目按照配置中的顺序应用。
+ * */
+#[derive(Debug, Default)]
+pub(crate) struct SpeechDict {
+    rules: Vec<DictRule>,
+}
+
+impl SpeechDict {
+    /**
+     * 从词典条目构建语音词典，无效的正则表达式会被跳过。
+     * `entries` 词典条目。
+     * */
+    pub(crate) fn build(entries: &[DictEntry]) -> Self {
+        let scoped = entries.iter().filter(|e| e.app.is_some());
+        let global = entries.iter().filter(|e| e.app.is_none());
+
+        let rules = scoped
+            .chain(global)
+            .filter_map(|e| {
+                let pattern = match e.kind {
+                    DictEntryKind::Literal => regex::escape(&e.pattern),
+                    DictEntryKind::WholeWord => regex::escape(&e.pattern),
+                    DictEntryKind::Regex => e.pattern.clone(),
+                };
+                match RegexBuilder::new(&pattern)
+                    .case_insensitive(!e.case_sensitive)
+                    .build()
+                {
+                    Ok(regex) => Some(DictRule {
+                        regex,
+                        replacement: e.replacement.clone(),
+                        expand: e.kind == DictEntryKind::Regex,
+                        whole_word: e.kind == DictEntryKind::WholeWord,
+                        app: e.app.as_ref().map(|a| a.to_lowercase()),
+                    }),
+                    Err(err) => {
+                        error!("Invalid speech dict pattern `{}`. {}", e.pattern, err);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    /**
+     * 判断是否存在只在指定应用程序中生效的规则。
+     * */
+    pub(crate) fn has_scoped_rules(&self) -> bool {
+        self.rules.iter().any(|r| r.app.is_some())
+    }
+
+    /**
+     * 应用词典到文字。
+     * `text` 原始文字。
+     * `app` 当前应用程序的进程名称。
+     * */
+    pub(crate) fn apply(&self, text: &str, app: Option<&str>) -> String {
+        let app = app.map(|a| a.to_lowercase());
+        let mut result = text.to_string();
+
+        for rule in self.rules.iter() {
+            if rule.app.is_some() && rule.app != app {
+                continue;
+            }
+            if rule.whole_word {
+                result = replace_whole_words(&rule.regex, &result, &rule.replacement);
+                continue;
+            }
+            let replaced = if rule.expand {
+                rule.regex.replace_all(&result, rule.replacement.as_str())
+            } else {
+                rule.regex
+                    .replace_all(&result, NoExpand(rule.replacement.as_str()))
+            };
+            result = replaced.into_owned();
+        }
+
+        result
+    }
+}
+
+// 替换完整的单词，匹配的位置不是完整的单词时，从下一个字符开始继续查找
+fn replace_whole_words(regex: &Regex, text: &str, replacement: &str) -> String {
+    let mut result = String::new();
+    let mut copied = 0;
+    let mut pos = 0;
+    while let Some(m) = regex.find_at(text, pos) {
+        if !m.is_empty() && is_whole_word(text, m.start(), m.end()) {
+            result.push_str(&text[copied..m.start()]);
+            result.push_str(replacement);
+            copied = m.end();
+            pos = m.end();
+            continue;
+        }
+        match text[m.start()..].chars().next() {
+            Some(c) => pos = m.start() + c.len_utf8(),
+            None => break,
+        }
+    }
+    result.push_str(&text[copied..]);
+
+    result
+}
+
+// 匹配的开头或结尾是单词字符时，相邻的字符不能也是单词字符；开头或结尾是符号时（例如C++和.NET）不需要检查
+fn is_whole_word(text: &str, start: usize, end: usize) -> bool {
+    let matched = &text[start..end];
+    let joined = |edge: Option<char>, neighbour: Option<char>| {
+        edge.is_some_and(is_word_char) && neighbour.is_some_and(is_word_char)
+    };
+    !joined(matched.chars().next(), text[..start].chars().next_back())
+        && !joined(matched.chars().next_back(), text[end..].chars().next())
+}
+
+// 中日韩文字之间没有单词的边界，所以不算作单词字符
+fn is_word_char(c: char) -> bool {
+    let cjk = matches!(
+        c as u32,
+        0x2E80..=0x9FFF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF | 0xFF66..=0xFF9F | 0x20000..=0x3FFFF
+    );
+    (c.is_alphanumeric() || c == '_') && !cjk
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(pattern: &str, replacement: &str, kind: DictEntryKind) -> DictEntry {
+        DictEntry {
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            kind,
+            case_sensitive: false,
+            app: None,
+        }
+    }
+
+    #[test]
+    fn test_literal_and_whole_word() {
+        let dict = SpeechDict::build(&[
+            entry("$", "dollar", DictEntryKind::Literal),
+            entry("vs", "versus", DictEntryKind::WholeWord),
+        ]);
+        assert_eq!(dict.apply("$5 VS vsCode", None), "dollar5 versus vsCode");
+    }
+
+    #[test]
+    fn test_regex_and_case() {
+        let mut e = entry(r"(\d+)px", "$1像素", DictEntryKind::Regex);
+        e.case_sensitive = true;
+        let dict = SpeechDict::build(&[e, entry("[", "x", DictEntryKind::Regex)]);
+        assert_eq!(dict.apply("12px 3PX", None), "12像素 3PX");
+    }
+
+    #[test]
+    fn test_app_scope_and_order() {
+        let mut scoped = entry("rs", "rust source", DictEntryKind::WholeWord);
+        scoped.app = Some("Code.exe".to_string());
+        let dict = SpeechDict::build(&[entry("rs", "rs file", DictEntryKind::WholeWord), scoped]);
+        assert!(dict.has_scoped_rules());
+        assert_eq!(dict.apply("main rs", Some("code.exe")), "main rust source");
+        assert_eq!(dict.apply("main rs", Some("notepad.exe")), "main rs file");
+    }
+
+    #[test]
+    fn test_whole_word_edges() {
+        let dict = SpeechDict::build(&[
+            entry("C++", "C plus plus", DictEntryKind::WholeWord),
+            entry(".NET", "dot net", DictEntryKind::WholeWord),
+            entry("ab", "AB", DictEntryKind::WholeWord),
+        ]);
+        assert_eq!(
+            dict.apply("use C++ or .NET, not xC++ or abab ab", None),
+            "use C plus plus or dot net, not xC++ or abab AB"
+        );
+    }
+
+    #[test]
+    fn test_whole_word_cjk() {
+        let dict = SpeechDict::build(&[
+            entry("中文", "汉语", DictEntryKind::WholeWord),
+            entry("vs", "对", DictEntryKind::WholeWord),
+        ]);
+        assert_eq!(dict.apply("学中文吧，甲vs乙", None), "学汉语吧，甲对乙");
+    }
+}
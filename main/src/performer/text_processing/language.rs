@@ -0,0 +1,88 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::configs::items::tts::Script;
+
+/**
+ * 获取字符所属的书写系统，数字、空白和西文标点这些中性字符返回None。
+ * `ch` 字符。
+ * */
+pub(crate) fn get_script(ch: char) -> Option<Script> {
+    match ch {
+        // 中日韩统一表意文字、扩展A区和兼容表意文字
+        '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '\u{F900}'..='\u{FAFF}' => {
+            Some(Script::Han)
+        }
+        // 中文标点和全角字符
+        '\u{3000}'..='\u{303F}' | '\u{FF00}'..='\u{FFEF}' | '“' | '”' | '‘' | '’' | '…' | '—' => {
+            Some(Script::Han)
+        }
+        c if c.is_alphabetic() && (c.is_ascii() || ('\u{00C0}'..='\u{024F}').contains(&c)) => {
+            Some(Script::Latin)
+        }
+        _ => None,
+    }
+}
+
+//noinspection StructuralWrap
+/**
+ * 把文字按照书写系统切分成多段，返回每一段的书写系统和内容。
+ * 中性字符（数字、空白和西文标点）并入前面的段落，位于开头的中性字符并入第一个段落；如果整段文字都是中性字符，则书写系统为None。
+ * `text` 需要切分的文字。
+ * */
+pub(crate) fn segment_by_script(text: &str) -> Vec<(Option<Script>, String)> {
+    let mut result: Vec<(Option<Script>, String)> = vec![];
+
+    for ch in text.chars() {
+        let script = get_script(ch);
+        match result.last_mut() {
+            Some((last, run)) if script.is_none() || *last == script => run.push(ch),
+            // 开头的中性字符并入第一个有书写系统的段落
+            Some((last, run)) if last.is_none() => {
+                *last = script;
+                run.push(ch);
+            }
+            _ => result.push((script, String::from(ch))),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_mixed() {
+        let runs = segment_by_script("打开 iPhone 15 的设置，然后 OK");
+        assert_eq!(
+            runs,
+            vec![
+                (Some(Script::Han), "打开 ".to_string()),
+                (Some(Script::Latin), "iPhone 15 ".to_string()),
+                (Some(Script::Han), "的设置，然后 ".to_string()),
+                (Some(Script::Latin), "OK".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_segment_neutral() {
+        assert_eq!(
+            segment_by_script("12. café"),
+            vec![(Some(Script::Latin), "12. café".to_string())]
+        );
+        assert_eq!(segment_by_script("3.14"), vec![(None, "3.14".to_string())]);
+    }
+}
@@ -0,0 +1,237 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::configs::items::tts::NumberMode;
+use regex::{Captures, Regex};
+use std::sync::LazyLock;
+
+const DIGITS: [char; 10] = ['零', '一', '二', '三', '四', '五', '六', '七', '八', '九'];
+const UNITS: [&str; 4] = ["", "十", "百", "千"];
+const SECTION_UNITS: [&str; 4] = ["", "万", "亿", "万亿"];
+
+/// 按照完整数值朗读时，整数部分最多支持的位数，超过的按位朗读
+const MAX_FULL_DIGITS: usize = 16;
+
+//noinspection StructuralWrap
+/**
+ * 按照数字的朗读方式把文字中的数字转换成中文读法，支持整数、小数、百分数、负数和千位分隔符（例如“1,234”）。
+ * 前后紧跟着逗号和数字时（例如“3,500,7”）逗号是列表的分隔符，每个数字单独朗读。
+ * `text` 原始文字。
+ * `mode` 数字的朗读方式。
+ * */
+pub(crate) fn normalize_numbers(text: &str, mode: NumberMode) -> String {
+    static NUMBER: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(-)?([0-9]{1,3}(?:,[0-9]{3})+|[0-9]+)(?:\.([0-9]+))?(%)?").unwrap()
+    });
+
+    if mode == NumberMode::Engine {
+        return text.to_string();
+    }
+
+    NUMBER
+        .replace_all(text, |caps: &Captures| {
+            let start = caps.get(0).unwrap().start();
+            // 减号前面紧跟着西文字母或数字时是连接符（例如“2024-01-01”），不是负号
+            let is_negative = caps.get(1).is_some()
+                && !text[..start]
+                    .chars()
+                    .next_back()
+                    .is_some_and(|c| c.is_ascii_alphanumeric());
+
+            let end = caps.get(0).unwrap().end();
+            let is_list = caps[2].contains(',')
+                && (is_list_separator(text[..start].chars().rev())
+                    || is_list_separator(text[end..].chars()));
+            let integer = caps[2].replace(',', "");
+            let parts = match is_list {
+                true => caps[2].split(',').collect(),
+                false => vec![integer.as_str()],
+            };
+            let mut result = String::new();
+            if caps.get(1).is_some() && !is_negative {
+                result.push('-');
+            }
+            // 负的百分数读作“负百分之五”
+            if is_negative {
+                result.push('负');
+            }
+            for (i, part) in parts.iter().enumerate() {
+                if i > 0 {
+                    result.push(',');
+                }
+                if i == parts.len() - 1 && caps.get(4).is_some() {
+                    result.push_str("百分之");
+                }
+                match mode {
+                    NumberMode::Full => result.push_str(&integer_to_chinese(part)),
+                    _ => result.push_str(&digits_to_chinese(part)),
+                }
+            }
+            if let Some(fraction) = caps.get(3) {
+                result.push('点');
+                result.push_str(&digits_to_chinese(fraction.as_str()));
+            }
+            result
+        })
+        .into_owned()
+}
+
+// 判断数字旁边是否是逗号和另一个数字，`chars`是从数字向外的字符
+fn is_list_separator(mut chars: impl Iterator<Item = char>) -> bool {
+    chars.next() == Some(',') && chars.next().is_some_and(|c| c.is_ascii_digit())
+}
+
+// 逐位读出数字
+fn digits_to_chinese(digits: &str) -> String {
+    digits
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .map(|d| DIGITS[d as usize])
+        .collect()
+}
+
+// 把整数转换成中文读法，以0开头或者位数过多的数字（例如编号）按位朗读
+fn integer_to_chinese(digits: &str) -> String {
+    if digits.len() > MAX_FULL_DIGITS || (digits.len() > 1 && digits.starts_with('0')) {
+        return digits_to_chinese(digits);
+    }
+    let Ok(mut value) = digits.parse::<u64>() else {
+        return digits_to_chinese(digits);
+    };
+    if value == 0 {
+        return DIGITS[0].to_string();
+    }
+
+    // 每四位一节，从低到高排列
+    let mut sections = vec![];
+    while value > 0 {
+        sections.push(value % 10000);
+        value /= 10000;
+    }
+
+    let mut result = String::new();
+    let mut need_zero = false;
+    for (i, section) in sections.iter().enumerate().rev() {
+        if *section == 0 {
+            need_zero = !result.is_empty();
+            continue;
+        }
+        if !result.is_empty() && (need_zero || *section < 1000) {
+            result.push(DIGITS[0]);
+        }
+        result.push_str(&section_to_chinese(*section));
+        result.push_str(SECTION_UNITS[i]);
+        need_zero = false;
+    }
+
+    // “一十二”习惯读作“十二”
+    match result.strip_prefix("一十") {
+        Some(rest) => format!("十{}", rest),
+        None => result,
+    }
+}
+
+// 转换四位以内的数字
+fn section_to_chinese(section: u64) -> String {
+    let digits = [
+        section / 1000,
+        section / 100 % 10,
+        section / 10 % 10,
+        section % 10,
+    ];
+    let mut result = String::new();
+    let mut need_zero = false;
+
+    for (i, d) in digits.iter().enumerate() {
+        if *d == 0 {
+            need_zero = !result.is_empty();
+            continue;
+        }
+        if need_zero {
+            result.push(DIGITS[0]);
+            need_zero = false;
+        }
+        result.push(DIGITS[*d as usize]);
+        result.push_str(UNITS[3 - i]);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_to_chinese() {
+        let cases = [
+            ("0", "零"),
+            ("10", "十"),
+            ("110", "一百一十"),
+            ("1004", "一千零四"),
+            ("1234", "一千二百三十四"),
+            ("100010", "十万零一十"),
+            ("100001000", "一亿零一千"),
+            ("120000000", "一亿二千万"),
+            ("007", "零零七"),
+        ];
+        for (digits, expected) in cases {
+            assert_eq!(integer_to_chinese(digits), expected);
+        }
+    }
+
+    #[test]
+    fn test_normalize_full() {
+        let text = "余额-1,234.50元，涨了12.5%，日期2024-01-01";
+        assert_eq!(
+            normalize_numbers(text, NumberMode::Full),
+            "余额负一千二百三十四点五零元，涨了百分之十二点五，日期二千零二十四-零一-零一"
+        );
+        assert_eq!(normalize_numbers(text, NumberMode::Engine), text);
+    }
+
+    #[test]
+    fn test_normalize_negative_percent() {
+        assert_eq!(
+            normalize_numbers("跌了-5%，利率-0.25%", NumberMode::Full),
+            "跌了负百分之五，利率负百分之零点二五"
+        );
+        assert_eq!(
+            normalize_numbers("变化-12%", NumberMode::Digits),
+            "变化负百分之一二"
+        );
+    }
+
+    #[test]
+    fn test_normalize_digits() {
+        assert_eq!(
+            normalize_numbers("电话13800138000，-3.5", NumberMode::Digits),
+            "电话一三八零零一三八零零零，负三点五"
+        );
+    }
+
+    #[test]
+    fn test_normalize_list() {
+        assert_eq!(
+            normalize_numbers(
+                "坐标3,500,7，列表1,2,3，12,345,6，x=1,234",
+                NumberMode::Full
+            ),
+            "坐标三,五百,七，列表一,二,三，十二,三百四十五,六，x=一千二百三十四"
+        );
+        assert_eq!(
+            normalize_numbers("3,500,7", NumberMode::Digits),
+            "三,五零零,七"
+        );
+    }
+}
@@ -0,0 +1,632 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use std::time::Duration;
+
+/// 韵律属性的值，读屏的取值范围是0到100
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum ProsodyValue {
+    /// 绝对值
+    Absolute(i32),
+    /// 相对于当前值的百分比变化，例如20表示提高20%
+    Relative(i32),
+}
+
+impl ProsodyValue {
+    // 根据当前值计算新的值
+    fn resolve(&self, base: i32) -> i32 {
+        match self {
+            Self::Absolute(v) => *v,
+            Self::Relative(p) => base + base * p / 100,
+        }
+        .clamp(0, 100)
+    }
+}
+
+/// 韵律属性（语速、音调和音量），没有设置的属性保持不变
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Prosody {
+    pub(crate) rate: Option<ProsodyValue>,
+    pub(crate) pitch: Option<ProsodyValue>,
+    pub(crate) volume: Option<ProsodyValue>,
+}
+
+/// 语音标记的节点
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum MarkupNode {
+    /// 普通文字
+    Text(String),
+    /// 停顿
+    Break(Duration),
+    /// 修改其中文字的韵律
+    Prosody(Prosody, Vec<MarkupNode>),
+    /// 逐个字符朗读的文字
+    Characters(String),
+    /// 使用指定的语音（语音ID或名称）朗读其中的文字
+    Voice(String, Vec<MarkupNode>),
+}
+
+/// 语音标记展开后的片段，每个片段的语音参数都是确定的
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum SpeechSegment {
+    Text {
+        text: String,
+        speed: i32,
+        pitch: i32,
+        volume: i32,
+        voice: Option<String>,
+    },
+    Break(Duration),
+}
+
+//noinspection StructuralWrap
+/**
+ * 语音标记，可以描述停顿、韵律变化、逐字朗读和语音切换。
+ * 支持SSML的引擎直接接收转换后的SSML，其他引擎按照展开后的片段分段朗读。
+ * */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct SpeechMarkup {
+    pub(crate) nodes: Vec<MarkupNode>,
+}
+
+// 解析过程中还没有结束的元素
+enum Frame {
+    Prosody(Prosody),
+    Characters,
+    Voice(String),
+    Break(Duration),
+    // 不认识或不需要处理的元素，只保留其中的内容
+    Other,
+}
+
+impl SpeechMarkup {
+    //noinspection StructuralWrap
+    /**
+     * 解析SSML的子集，支持speak、break、prosody（rate、pitch、volume）、say-as（characters）和voice元素，其他元素只保留其中的文字。
+     * 如果标记的格式不正确，则返回错误信息。
+     * `ssml` SSML文本。
+     * */
+    pub(crate) fn parse(ssml: &str) -> Result<Self, String> {
+        // 栈中的每一项是（元素名称，元素，已经解析的子节点）
+        let mut stack: Vec<(String, Frame, Vec<MarkupNode>)> =
+            vec![(String::new(), Frame::Other, vec![])];
+        let mut rest = ssml;
+
+        while !rest.is_empty() {
+            let Some(start) = rest.find('<') else {
+                push_text(&mut stack.last_mut().unwrap().2, &unescape(rest));
+                break;
+            };
+            if start > 0 {
+                push_text(&mut stack.last_mut().unwrap().2, &unescape(&rest[..start]));
+            }
+            rest = &rest[start..];
+
+            // 注释和XML声明
+            if let Some(r) = rest.strip_prefix("<!--") {
+                let end = r.find("-->").ok_or("Unclosed comment.")?;
+                rest = &r[end + 3..];
+                continue;
+            }
+            if let Some(r) = rest.strip_prefix("<?") {
+                let end = r.find("?>").ok_or("Unclosed declaration.")?;
+                rest = &r[end + 2..];
+                continue;
+            }
+
+            let end = find_tag_end(rest).ok_or("Unclosed tag.")?;
+            let tag = &rest[1..end];
+            rest = &rest[end + 1..];
+
+            if let Some(name) = tag.strip_prefix('/') {
+                let name = name.trim();
+                let (open, frame, children) = stack.pop().unwrap();
+                if stack.is_empty() || open != name {
+                    return Err(format!("Unexpected closing tag `{}`.", name));
+                }
+                let parent = &mut stack.last_mut().unwrap().2;
+                close_frame(parent, frame, children);
+                continue;
+            }
+
+            let (tag, self_closing) = match tag.strip_suffix('/') {
+                Some(t) => (t, true),
+                None => (tag, false),
+            };
+            let (name, attrs) = parse_tag(tag)?;
+            let attr = |key: &str| {
+                attrs
+                    .iter()
+                    .find(|(k, _)| k == key)
+                    .map(|(_, v)| v.as_str())
+            };
+            let frame = match name.as_str() {
+                "break" => Frame::Break(parse_break(attr("time"), attr("strength"))),
+                "prosody" => Frame::Prosody(Prosody {
+                    rate: attr("rate").and_then(parse_rate),
+                    pitch: attr("pitch").and_then(parse_pitch),
+                    volume: attr("volume").and_then(parse_volume),
+                }),
+                "say-as" if attr("interpret-as") == Some("characters") => Frame::Characters,
+                "voice" => match attr("name") {
+                    Some(v) => Frame::Voice(v.to_string()),
+                    None => Frame::Other,
+                },
+                _ => Frame::Other,
+            };
+
+            if self_closing {
+                close_frame(&mut stack.last_mut().unwrap().2, frame, vec![]);
+            } else {
+                stack.push((name, frame, vec![]));
+            }
+        }
+
+        if stack.len() > 1 {
+            return Err(format!("Unclosed tag `{}`.", stack.last().unwrap().0));
+        }
+        let (_, _, nodes) = stack.pop().unwrap();
+
+        Ok(Self { nodes })
+    }
+
+    //noinspection StructuralWrap
+    /**
+     * 转换成SSML文本，交给支持SSML的引擎朗读。
+     * 绝对的语速按照默认值50为1倍换算成倍数，绝对的音调换算成相对于50的百分比，音量直接使用0到100的数值。
+     * */
+    pub(crate) fn to_ssml(&self) -> String {
+        let mut result = String::from(
+            r#"<speak version="1.0" xmlns="http://www.w3.org/2001/10/synthesis" xml:lang="zh-CN">"#,
+        );
+        write_ssml(&mut result, &self.nodes);
+        result.push_str("</speak>");
+
+        result
+    }
+
+    //noinspection StructuralWrap
+    /**
+     * 处理其中的所有普通文字，返回处理后的语音标记，逐个字符朗读的文字保持不变。
+     * `f` 处理文字的函数。
+     * */
+    pub(crate) fn map_text(&self, f: &impl Fn(&str) -> String) -> Self {
+        Self {
+            nodes: map_nodes(&self.nodes, f),
+        }
+    }
+
+    //noinspection StructuralWrap
+    /**
+     * 展开成语音参数确定的片段，相邻的、参数相同的文字会合并在一起。
+     * `speed` 当前的语速。
+     * `pitch` 当前的音调。
+     * `volume` 当前的音量。
+     * */
+    pub(crate) fn flatten(&self, speed: i32, pitch: i32, volume: i32) -> Vec<SpeechSegment> {
+        let mut result = vec![];
+        flatten_nodes(&mut result, &self.nodes, (speed, pitch, volume), &None);
+
+        result
+    }
+}
+
+fn map_nodes(nodes: &[MarkupNode], f: &impl Fn(&str) -> String) -> Vec<MarkupNode> {
+    nodes
+        .iter()
+        .map(|node| match node {
+            MarkupNode::Text(t) => MarkupNode::Text(f(t)),
+            MarkupNode::Prosody(p, children) => {
+                MarkupNode::Prosody(p.clone(), map_nodes(children, f))
+            }
+            MarkupNode::Voice(v, children) => MarkupNode::Voice(v.clone(), map_nodes(children, f)),
+            x => x.clone(),
+        })
+        .collect()
+}
+
+// 添加文字节点，和前面的文字节点合并
+fn push_text(nodes: &mut Vec<MarkupNode>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match nodes.last_mut() {
+        Some(MarkupNode::Text(last)) => last.push_str(text),
+        _ => nodes.push(MarkupNode::Text(text.to_string())),
+    }
+}
+
+// 元素结束，把他转换成节点添加到父元素中
+fn close_frame(parent: &mut Vec<MarkupNode>, frame: Frame, children: Vec<MarkupNode>) {
+    match frame {
+        Frame::Prosody(p) => parent.push(MarkupNode::Prosody(p, children)),
+        Frame::Voice(v) => parent.push(MarkupNode::Voice(v, children)),
+        Frame::Break(d) => parent.push(MarkupNode::Break(d)),
+        Frame::Characters => {
+            let mut text = String::new();
+            collect_text(&mut text, &children);
+            parent.push(MarkupNode::Characters(text));
+        }
+        Frame::Other => children.into_iter().for_each(|n| match n {
+            MarkupNode::Text(t) => push_text(parent, &t),
+            n => parent.push(n),
+        }),
+    }
+}
+
+fn collect_text(result: &mut String, nodes: &[MarkupNode]) {
+    for node in nodes {
+        match node {
+            MarkupNode::Text(t) | MarkupNode::Characters(t) => result.push_str(t),
+            MarkupNode::Prosody(_, c) | MarkupNode::Voice(_, c) => collect_text(result, c),
+            MarkupNode::Break(_) => {}
+        }
+    }
+}
+
+// 查找标签结束的位置，属性值中的“>”不算
+fn find_tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+// 解析标签的名称和属性
+fn parse_tag(tag: &str) -> Result<(String, Vec<(String, String)>), String> {
+    let tag = tag.trim();
+    let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let name = tag[..name_end].to_string();
+    if name.is_empty() {
+        return Err("Empty tag name.".to_string());
+    }
+
+    let mut attrs = vec![];
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let eq = rest
+            .find('=')
+            .ok_or(format!("Invalid attribute in `{}`.", name))?;
+        let key = rest[..eq].trim().to_string();
+        let value = rest[eq + 1..].trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+            .ok_or(format!("Unquoted attribute `{}`.", key))?;
+        let end = value[1..]
+            .find(quote)
+            .ok_or(format!("Unclosed attribute `{}`.", key))?;
+        attrs.push((key, unescape(&value[1..end + 1])));
+        rest = value[end + 2..].trim_start();
+    }
+
+    Ok((name, attrs))
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// 解析停顿的时长，支持毫秒和秒，或者停顿的强度
+fn parse_break(time: Option<&str>, strength: Option<&str>) -> Duration {
+    let time = time.and_then(|t| {
+        let t = t.trim();
+        if let Some(ms) = t.strip_suffix("ms") {
+            ms.trim().parse::<f64>().ok().map(|v| v / 1000.0)
+        } else {
+            t.strip_suffix('s')?.trim().parse::<f64>().ok()
+        }
+    });
+    let secs = match time {
+        Some(t) => t.max(0.0),
+        None => match strength.unwrap_or("medium") {
+            "none" => 0.0,
+            "x-weak" => 0.1,
+            "weak" => 0.2,
+            "strong" => 0.7,
+            "x-strong" => 1.0,
+            _ => 0.4,
+        },
+    };
+
+    Duration::from_secs_f64(secs)
+}
+
+// 解析百分比形式的值，“+20%”和“-20%”是相对变化，“120%”相当于“+20%”
+fn parse_percent(value: &str) -> Option<ProsodyValue> {
+    let number = value.strip_suffix('%')?.trim();
+    let v = number.parse::<f64>().ok()? as i32;
+    match number.starts_with(['+', '-']) {
+        true => Some(ProsodyValue::Relative(v)),
+        false => Some(ProsodyValue::Relative(v - 100)),
+    }
+}
+
+fn parse_rate(value: &str) -> Option<ProsodyValue> {
+    let value = value.trim();
+    match value {
+        "x-slow" => Some(ProsodyValue::Absolute(10)),
+        "slow" => Some(ProsodyValue::Absolute(30)),
+        "medium" => Some(ProsodyValue::Absolute(50)),
+        "fast" => Some(ProsodyValue::Absolute(70)),
+        "x-fast" => Some(ProsodyValue::Absolute(90)),
+        // 没有单位的数字是语速的倍数
+        _ => parse_percent(value).or_else(|| {
+            let v = value.parse::<f64>().ok()?;
+            Some(ProsodyValue::Relative(((v - 1.0) * 100.0) as i32))
+        }),
+    }
+}
+
+fn parse_pitch(value: &str) -> Option<ProsodyValue> {
+    let value = value.trim();
+    match value {
+        "x-low" => Some(ProsodyValue::Absolute(10)),
+        "low" => Some(ProsodyValue::Absolute(30)),
+        "medium" => Some(ProsodyValue::Absolute(50)),
+        "high" => Some(ProsodyValue::Absolute(70)),
+        "x-high" => Some(ProsodyValue::Absolute(90)),
+        _ => parse_percent(value),
+    }
+}
+
+fn parse_volume(value: &str) -> Option<ProsodyValue> {
+    let value = value.trim();
+    match value {
+        "silent" => Some(ProsodyValue::Absolute(0)),
+        "x-soft" => Some(ProsodyValue::Absolute(20)),
+        "soft" => Some(ProsodyValue::Absolute(40)),
+        "medium" => Some(ProsodyValue::Absolute(60)),
+        "loud" => Some(ProsodyValue::Absolute(80)),
+        "x-loud" => Some(ProsodyValue::Absolute(100)),
+        // 没有单位的数字是0到100的绝对音量
+        _ => parse_percent(value).or_else(|| {
+            let v = value.parse::<f64>().ok()?;
+            Some(ProsodyValue::Absolute(v as i32))
+        }),
+    }
+}
+
+fn format_relative(p: i32) -> String {
+    match p >= 0 {
+        true => format!("+{}%", p),
+        false => format!("{}%", p),
+    }
+}
+
+fn write_ssml(result: &mut String, nodes: &[MarkupNode]) {
+    for node in nodes {
+        match node {
+            MarkupNode::Text(t) => result.push_str(&escape(t)),
+            MarkupNode::Break(d) => {
+                result.push_str(&format!(r#"<break time="{}ms"/>"#, d.as_millis()))
+            }
+            MarkupNode::Characters(t) => result.push_str(&format!(
+                r#"<say-as interpret-as="characters">{}</say-as>"#,
+                escape(t)
+            )),
+            MarkupNode::Voice(v, children) => {
+                result.push_str(&format!(r#"<voice name="{}">"#, escape(v)));
+                write_ssml(result, children);
+                result.push_str("</voice>");
+            }
+            MarkupNode::Prosody(p, children) => {
+                result.push_str("<prosody");
+                if let Some(v) = p.rate {
+                    let v = match v {
+                        ProsodyValue::Absolute(v) => format!("{:.2}", v as f64 / 50.0),
+                        ProsodyValue::Relative(p) => format_relative(p),
+                    };
+                    result.push_str(&format!(r#" rate="{}""#, v));
+                }
+                if let Some(v) = p.pitch {
+                    let v = match v {
+                        ProsodyValue::Absolute(v) => format_relative((v - 50) * 2),
+                        ProsodyValue::Relative(p) => format_relative(p),
+                    };
+                    result.push_str(&format!(r#" pitch="{}""#, v));
+                }
+                if let Some(v) = p.volume {
+                    let v = match v {
+                        ProsodyValue::Absolute(v) => v.to_string(),
+                        ProsodyValue::Relative(p) => format_relative(p),
+                    };
+                    result.push_str(&format!(r#" volume="{}""#, v));
+                }
+                result.push('>');
+                write_ssml(result, children);
+                result.push_str("</prosody>");
+            }
+        }
+    }
+}
+
+// 添加文字片段，和前面参数相同的文字片段合并
+fn push_segment_text(
+    result: &mut Vec<SpeechSegment>,
+    text: &str,
+    params: (i32, i32, i32),
+    voice: &Option<String>,
+) {
+    if let Some(SpeechSegment::Text {
+        text: last,
+        speed,
+        pitch,
+        volume,
+        voice: last_voice,
+    }) = result.last_mut()
+    {
+        if (*speed, *pitch, *volume) == params && last_voice == voice {
+            last.push_str(text);
+            return;
+        }
+    }
+    result.push(SpeechSegment::Text {
+        text: text.to_string(),
+        speed: params.0,
+        pitch: params.1,
+        volume: params.2,
+        voice: voice.clone(),
+    });
+}
+
+fn flatten_nodes(
+    result: &mut Vec<SpeechSegment>,
+    nodes: &[MarkupNode],
+    params: (i32, i32, i32),
+    voice: &Option<String>,
+) {
+    let (speed, pitch, volume) = params;
+
+    for node in nodes {
+        match node {
+            MarkupNode::Text(t) => push_segment_text(result, t, params, voice),
+            MarkupNode::Characters(t) => {
+                // 字符之间用空格隔开，引擎就会逐个朗读
+                let chars: Vec<String> = t.chars().map(String::from).collect();
+                push_segment_text(result, &format!(" {} ", chars.join(" ")), params, voice)
+            }
+            MarkupNode::Break(d) => result.push(SpeechSegment::Break(*d)),
+            MarkupNode::Prosody(p, children) => {
+                let params = (
+                    p.rate.map_or(speed, |v| v.resolve(speed)),
+                    p.pitch.map_or(pitch, |v| v.resolve(pitch)),
+                    p.volume.map_or(volume, |v| v.resolve(volume)),
+                );
+                flatten_nodes(result, children, params, voice)
+            }
+            MarkupNode::Voice(v, children) => {
+                flatten_nodes(result, children, params, &Some(v.clone()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let markup = SpeechMarkup::parse(
+            r#"<?xml version="1.0"?><speak>按钮<break time="300ms"/><prosody rate="+20%" pitch='high'>确定 &amp; 取消</prosody><say-as interpret-as="characters">OK</say-as><emphasis>！</emphasis></speak>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            markup.nodes,
+            vec![
+                MarkupNode::Text("按钮".to_string()),
+                MarkupNode::Break(Duration::from_millis(300)),
+                MarkupNode::Prosody(
+                    Prosody {
+                        rate: Some(ProsodyValue::Relative(20)),
+                        pitch: Some(ProsodyValue::Absolute(70)),
+                        volume: None,
+                    },
+                    vec![MarkupNode::Text("确定 & 取消".to_string())]
+                ),
+                MarkupNode::Characters("OK".to_string()),
+                MarkupNode::Text("！".to_string()),
+            ]
+        );
+        assert!(SpeechMarkup::parse("<speak><prosody>a</speak>").is_err());
+        assert!(SpeechMarkup::parse("<speak>a").is_err());
+    }
+
+    #[test]
+    fn test_flatten() {
+        let markup = SpeechMarkup::parse(
+            r#"a<prosody volume="soft">b<voice name="en">c</voice></prosody><break/><prosody rate="150%">d</prosody>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            markup.flatten(50, 50, 100),
+            vec![
+                SpeechSegment::Text {
+                    text: "a".to_string(),
+                    speed: 50,
+                    pitch: 50,
+                    volume: 100,
+                    voice: None
+                },
+                SpeechSegment::Text {
+                    text: "b".to_string(),
+                    speed: 50,
+                    pitch: 50,
+                    volume: 40,
+                    voice: None
+                },
+                SpeechSegment::Text {
+                    text: "c".to_string(),
+                    speed: 50,
+                    pitch: 50,
+                    volume: 40,
+                    voice: Some("en".to_string())
+                },
+                SpeechSegment::Break(Duration::from_millis(400)),
+                SpeechSegment::Text {
+                    text: "d".to_string(),
+                    speed: 75,
+                    pitch: 50,
+                    volume: 100,
+                    voice: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_ssml() {
+        let markup =
+            SpeechMarkup::parse(r#"<prosody pitch="x-high" volume="80">A&lt;B</prosody>"#).unwrap();
+        assert!(markup
+            .to_ssml()
+            .ends_with(r#"><prosody pitch="+80%" volume="80">A&lt;B</prosody></speak>"#));
+    }
+
+    #[test]
+    fn test_map_text() {
+        let markup = SpeechMarkup::parse(
+            r#"第1<voice name="a"><prosody rate="fast">第2</prosody></voice><say-as interpret-as="characters">3</say-as>"#,
+        )
+        .unwrap();
+        let markup = markup.map_text(&|t| t.replace('1', "一").replace('2', "二"));
+        assert_eq!(
+            markup.to_ssml(),
+            SpeechMarkup::parse(
+                r#"第一<voice name="a"><prosody rate="fast">第二</prosody></voice><say-as interpret-as="characters">3</say-as>"#,
+            )
+            .unwrap()
+            .to_ssml()
+        );
+    }
+}
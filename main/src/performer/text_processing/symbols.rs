@@ -0,0 +1,212 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::configs::items::tts::SymbolLevel;
+use std::{collections::HashMap, sync::LazyLock};
+
+/// 同一个符号连续出现多少次以后合并朗读（例如“等于 6个”）
+const REPEAT_THRESHOLD: usize = 4;
+
+/**
+ * 获取符号的名称和朗读他需要的最低级别，如果不是符号则返回None。
+ * `ch` 字符。
+ * */
+pub(crate) fn get_symbol(ch: &char) -> Option<&'static (&'static str, SymbolLevel)> {
+    get_symbol_map().get(ch)
+}
+
+//noinspection StructuralWrap
+/**
+ * 按照朗读级别处理文字中的符号，级别足够的符号替换成他的名称，连续重复的符号合并成“名称 n个”，其他的符号保持原样交给语音引擎。
+ * 数字中间的小数点和千位分隔符不做处理。
+ * `text` 原始文字。
+ * `level` 当前的符号朗读级别。
+ * */
+pub(crate) fn process_symbols(text: &str, level: SymbolLevel) -> String {
+    if level == SymbolLevel::Off {
+        return text.to_string();
+    }
+    // 字符级别的符号（例如空格和换行）只在逐字朗读时读出，句子中按照All级别处理
+    let level = level.min(SymbolLevel::All);
+
+    let chars: Vec<char> = text.chars().collect();
+    let mut result = String::with_capacity(text.len());
+    let mut i = 0;
+    // 结尾是否是名称后面加上的空格，原文中的空白字符需要保留
+    let mut padded = false;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        let Some((name, min_level)) = get_symbol(&ch).filter(|(_, l)| *l <= level) else {
+            result.push(ch);
+            padded = false;
+            i += 1;
+            continue;
+        };
+        if is_number_separator(&chars, i) {
+            result.push(ch);
+            padded = false;
+            i += 1;
+            continue;
+        }
+
+        let count = chars[i..].iter().take_while(|c| **c == ch).count();
+        if count >= REPEAT_THRESHOLD {
+            push_name(&mut result, &format!("{} {}个", name, count));
+        } else {
+            (0..count).for_each(|_| push_name(&mut result, name));
+        }
+        padded = true;
+        // 停顿类的标点保留原样，让语音引擎正确断句
+        if *min_level == SymbolLevel::All {
+            result.push(ch);
+            padded = false;
+        }
+        i += count;
+    }
+
+    if padded {
+        result.pop();
+    }
+    result
+}
+
+// 在名称前后加上空格，避免和相邻的文字连读
+fn push_name(result: &mut String, name: &str) {
+    if !result.is_empty() && !result.ends_with(char::is_whitespace) {
+        result.push(' ');
+    }
+    result.push_str(name);
+    result.push(' ');
+}
+
+// 判断是否是数字中间的小数点或千位分隔符，例如“3.14”和“1,000”
+fn is_number_separator(chars: &[char], index: usize) -> bool {
+    matches!(chars[index], '.' | ',')
+        && index > 0
+        && chars[index - 1].is_ascii_digit()
+        && chars.get(index + 1).is_some_and(|c| c.is_ascii_digit())
+}
+
+fn get_symbol_map() -> &'static HashMap<char, (&'static str, SymbolLevel)> {
+    static MAP: LazyLock<HashMap<char, (&'static str, SymbolLevel)>> =
+        LazyLock::new(|| SYMBOL_DATA.iter().map(|(k, v, l)| (*k, (*v, *l))).collect());
+    &MAP
+}
+
+// 符号表：符号、名称和朗读他需要的最低级别
+const SYMBOL_DATA: &[(char, &str, SymbolLevel)] = &[
+    (' ', "空格", SymbolLevel::Character),
+    ('\t', "制表符", SymbolLevel::Character),
+    ('\n', "换行", SymbolLevel::Character),
+    ('!', "叹号", SymbolLevel::All),
+    ('！', "叹号", SymbolLevel::All),
+    ('"', "双引号", SymbolLevel::Most),
+    ('“', "左双引号", SymbolLevel::Most),
+    ('”', "右双引号", SymbolLevel::Most),
+    ('#', "井号", SymbolLevel::Low),
+    ('$', "美元", SymbolLevel::Low),
+    ('￥', "人民币", SymbolLevel::Low),
+    ('%', "百分号", SymbolLevel::Low),
+    ('&', "和", SymbolLevel::Low),
+    ('\'', "单引号", SymbolLevel::Most),
+    ('‘', "左单引号", SymbolLevel::Most),
+    ('’', "右单引号", SymbolLevel::Most),
+    ('(', "左括号", SymbolLevel::Most),
+    ('（', "左括号", SymbolLevel::Most),
+    (')', "右括号", SymbolLevel::Most),
+    ('）', "右括号", SymbolLevel::Most),
+    ('*', "星", SymbolLevel::Low),
+    ('+', "加", SymbolLevel::Low),
+    (',', "逗号", SymbolLevel::All),
+    ('，', "逗号", SymbolLevel::All),
+    ('-', "减", SymbolLevel::Most),
+    ('.', "点", SymbolLevel::All),
+    ('。', "句号", SymbolLevel::All),
+    ('/', "斜杠", SymbolLevel::Low),
+    (':', "冒号", SymbolLevel::All),
+    ('：', "冒号", SymbolLevel::All),
+    (';', "分号", SymbolLevel::All),
+    ('；', "分号", SymbolLevel::All),
+    ('<', "小于", SymbolLevel::Low),
+    ('《', "左书名号", SymbolLevel::Most),
+    ('=', "等于", SymbolLevel::Low),
+    ('>', "大于", SymbolLevel::Low),
+    ('》', "右书名号", SymbolLevel::Most),
+    ('?', "问号", SymbolLevel::All),
+    ('？', "问号", SymbolLevel::All),
+    ('@', "艾特", SymbolLevel::Low),
+    ('[', "左方括号", SymbolLevel::Most),
+    ('【', "左方括号", SymbolLevel::Most),
+    ('\\', "反斜杠", SymbolLevel::Most),
+    ('、', "顿号", SymbolLevel::All),
+    (']', "右方括号", SymbolLevel::Most),
+    ('】', "右方括号", SymbolLevel::Most),
+    ('^', "上尖号", SymbolLevel::Most),
+    ('…', "省略号", SymbolLevel::All),
+    ('_', "下划线", SymbolLevel::Most),
+    ('—', "破折号", SymbolLevel::Most),
+    ('`', "反撇号", SymbolLevel::Most),
+    ('·', "圆点", SymbolLevel::Most),
+    ('{', "左花括号", SymbolLevel::Most),
+    ('|', "竖杠", SymbolLevel::Most),
+    ('}', "右花括号", SymbolLevel::Most),
+    ('~', "波浪号", SymbolLevel::Most),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels() {
+        let text = "a=b, (c)";
+        assert_eq!(process_symbols(text, SymbolLevel::Off), text);
+        assert_eq!(process_symbols(text, SymbolLevel::Low), "a 等于 b, (c)");
+        assert_eq!(
+            process_symbols(text, SymbolLevel::Most),
+            "a 等于 b, 左括号 c 右括号"
+        );
+        assert_eq!(
+            process_symbols(text, SymbolLevel::All),
+            "a 等于 b 逗号 , 左括号 c 右括号"
+        );
+        assert_eq!(
+            process_symbols(text, SymbolLevel::Character),
+            process_symbols(text, SymbolLevel::All)
+        );
+    }
+
+    #[test]
+    fn test_repeat_and_numbers() {
+        assert_eq!(
+            process_symbols("标题\n======", SymbolLevel::Low),
+            "标题\n等于 6个"
+        );
+        assert_eq!(
+            process_symbols("**粗体**", SymbolLevel::Low),
+            "星 星 粗体 星 星"
+        );
+        assert_eq!(
+            process_symbols("3.14, 1,000", SymbolLevel::All),
+            "3.14 逗号 , 1,000"
+        );
+    }
+
+    #[test]
+    fn test_keep_trailing_whitespace() {
+        assert_eq!(process_symbols("a=b ", SymbolLevel::Low), "a 等于 b ");
+        assert_eq!(process_symbols("a=\n", SymbolLevel::Low), "a 等于 \n");
+        assert_eq!(process_symbols("a=", SymbolLevel::Low), "a 等于");
+    }
+}
@@ -0,0 +1,248 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::{configs::items::tts::Script, performer::text_processing::language::get_script};
+use std::{collections::HashSet, ops::Range};
+
+/// 中文分词时词语的最大长度
+const MAX_WORD_LEN: usize = 4;
+
+/// 句子结束的标点
+const SENTENCE_ENDS: &[char] = &['。', '！', '？', '!', '?', '；', ';', '…', '\n'];
+
+/// 分句的标点，过长的句子在这些标点后面切分
+const CLAUSE_ENDS: &[char] = &['，', '、', '：', ',', ':', '—'];
+
+/// 可以跟在句末标点后面的右引号和右括号
+const CLOSING_MARKS: &[char] = &['”', '’', '」', '』', '）', ')', '"', '\''];
+
+//noinspection StructuralWrap
+/**
+ * 把文字切分成词语，返回每个词语在字符列表中的范围。
+ * 连续的字母和数字是一个词；汉字使用正向最大匹配法在词库中查找词语，查不到的汉字单独成词；其他符号单独成词；空白字符会被跳过。
+ * `chars` 字符列表。
+ * `lexicon` 中文词库。
+ * */
+pub(crate) fn split_words(chars: &[char], lexicon: &HashSet<String>) -> Vec<Range<usize>> {
+    let mut result = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        if ch.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let end = if get_script(ch) == Some(Script::Han) && ch.is_alphabetic() {
+            // 从最长的候选词开始匹配
+            let max = (i + MAX_WORD_LEN).min(chars.len());
+            (i + 2..=max)
+                .rev()
+                .find(|end| lexicon.contains(&chars[i..*end].iter().collect::<String>()))
+                .unwrap_or(i + 1)
+        } else if ch.is_alphanumeric() && ch.is_ascii() || get_script(ch) == Some(Script::Latin) {
+            let mut end = i + 1;
+            while end < chars.len() && is_word_char(&chars[end], chars.get(end + 1)) {
+                end += 1;
+            }
+            end
+        } else {
+            i + 1
+        };
+
+        result.push(i..end);
+        i = end;
+    }
+
+    result
+}
+
+// 判断字符是否属于西文单词，单词中间的撇号（例如don't）也算在内
+fn is_word_char(ch: &char, next: Option<&char>) -> bool {
+    match ch {
+        '\'' | '’' => next.is_some_and(|c| c.is_alphanumeric() && c.is_ascii()),
+        c => c.is_alphanumeric() && (c.is_ascii() || get_script(*c) == Some(Script::Latin)),
+    }
+}
+
+/**
+ * 把文字切分成句子，返回每个句子在字符列表中的范围，句子开头的空白会被跳过。
+ * `chars` 字符列表。
+ * */
+pub(crate) fn split_sentences(chars: &[char]) -> Vec<Range<usize>> {
+    let mut result = vec![];
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        // 西文句号后面需要跟着空白或者位于结尾，避免切开小数和网址
+        let is_end = SENTENCE_ENDS.contains(&ch)
+            || (ch == '.' && chars.get(i + 1).map_or(true, |c| c.is_whitespace()));
+        i += 1;
+        if !is_end {
+            continue;
+        }
+
+        while i < chars.len() && (chars[i] == ch || CLOSING_MARKS.contains(&chars[i])) {
+            i += 1;
+        }
+        push_trimmed(&mut result, chars, start..i);
+        start = i;
+    }
+    push_trimmed(&mut result, chars, start..chars.len());
+
+    result
+}
+
+//noinspection StructuralWrap
+/**
+ * 把长文字切分成适合分段合成的片段，返回每个片段在字符列表中的范围。
+ * 先按照句子切分，超过最大长度的句子在最后一个分句标点处切开，没有标点时在空白处切开，都没有时直接按照最大长度切开。
+ * `chars` 字符列表。
+ * `max_len` 每个片段最多的字符数。
+ * */
+pub(crate) fn split_segments(chars: &[char], max_len: usize) -> Vec<Range<usize>> {
+    let max_len = max_len.max(1);
+    let mut result = vec![];
+
+    for sentence in split_sentences(chars) {
+        let mut start = sentence.start;
+        while sentence.end - start > max_len {
+            let limit = start + max_len;
+            let end = (start + 1..=limit)
+                .rev()
+                .find(|i| CLAUSE_ENDS.contains(&chars[i - 1]))
+                .or_else(|| (start + 1..limit).rev().find(|i| chars[*i].is_whitespace()))
+                .unwrap_or(limit);
+            push_trimmed(&mut result, chars, start..end);
+            start = end;
+        }
+        push_trimmed(&mut result, chars, start..sentence.end);
+    }
+
+    result
+}
+
+/**
+ * 把文字切分成行，返回每一行在字符列表中的范围，空白的行会被跳过。
+ * `chars` 字符列表。
+ * */
+pub(crate) fn split_lines(chars: &[char]) -> Vec<Range<usize>> {
+    let mut result = vec![];
+    let mut start = 0;
+
+    for (i, ch) in chars.iter().enumerate() {
+        if *ch == '\n' {
+            push_trimmed(&mut result, chars, start..i);
+            start = i + 1;
+        }
+    }
+    push_trimmed(&mut result, chars, start..chars.len());
+
+    result
+}
+
+// 去掉范围两端的空白字符，如果剩下的内容不为空则加入结果
+fn push_trimmed(result: &mut Vec<Range<usize>>, chars: &[char], range: Range<usize>) {
+    let Range { mut start, mut end } = range;
+    while start < end && chars[start].is_whitespace() {
+        start += 1;
+    }
+    while end > start && chars[end - 1].is_whitespace() {
+        end -= 1;
+    }
+    if start < end {
+        result.push(start..end);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(chars: &[char], ranges: Vec<Range<usize>>) -> Vec<String> {
+        ranges
+            .into_iter()
+            .map(|r| chars[r].iter().collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_split_words() {
+        let lexicon = ["读屏", "软件", "读屏软件"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let chars: Vec<char> = "好用的读屏软件, don't stop 3.5".chars().collect();
+        assert_eq!(
+            collect(&chars, split_words(&chars, &lexicon)),
+            vec![
+                "好",
+                "用",
+                "的",
+                "读屏软件",
+                ",",
+                "don't",
+                "stop",
+                "3",
+                ".",
+                "5"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_sentences() {
+        let chars: Vec<char> = "他说：“好的。”然后走了！Pi is 3.14. OK".chars().collect();
+        assert_eq!(
+            collect(&chars, split_sentences(&chars)),
+            vec!["他说：“好的。”", "然后走了！", "Pi is 3.14.", "OK"]
+        );
+    }
+
+    #[test]
+    fn test_split_segments() {
+        let chars: Vec<char> =
+            "第一句。这是一个很长的句子，需要在逗号处切开。Hello wonderful world"
+                .chars()
+                .collect();
+        assert_eq!(
+            collect(&chars, split_segments(&chars, 12)),
+            vec![
+                "第一句。",
+                "这是一个很长的句子，",
+                "需要在逗号处切开。",
+                "Hello",
+                "wonderful",
+                "world"
+            ]
+        );
+        // 没有标点和空白时直接按照长度切开
+        let chars: Vec<char> = "一二三四五六七".chars().collect();
+        assert_eq!(
+            collect(&chars, split_segments(&chars, 3)),
+            vec!["一二三", "四五六", "七"]
+        );
+    }
+
+    #[test]
+    fn test_split_lines() {
+        let chars: Vec<char> = "第一行\r\n\n  第二行  \n".chars().collect();
+        assert_eq!(
+            collect(&chars, split_lines(&chars)),
+            vec!["第一行", "第二行"]
+        );
+    }
+}
@@ -11,22 +11,41 @@
  * See the License for the specific language governing permissions and limitations under the License.
  */
 
+pub(crate) mod command;
+pub(crate) mod pcm_cache;
 pub(crate) mod sapi5;
 //noinspection SpellCheckingInspection
 pub(crate) mod vvtts;
 
 use crate::{
-    configs::items::tts::{TtsConfig, TtsPropertyItem},
+    configs::items::{
+        sound::SoundEvent,
+        tts::{TtsConfig, TtsPropertyItem},
+        voice_profile::VoiceProfile,
+    },
     context::{Context, ContextAccessor},
-    performer::text_processing::transform_single_char,
+    performer::audio::PcmFormat,
+    performer::sound::SoundArgument,
+    performer::text_processing::{
+        capital::{is_single_capital, mark_capitals, mark_spelled_capitals},
+        dict::SpeechDict,
+        language::segment_by_script,
+        numbers::normalize_numbers,
+        ssml::{SpeechMarkup, SpeechSegment},
+        symbols::process_symbols,
+        transform_single_char,
+        units::split_segments,
+    },
 };
 use arc_swap::ArcSwapAny;
-use parking_lot::RwLock;
+use log::{error, warn};
+use parking_lot::{Mutex, RwLock};
+use pcm_cache::{PcmCache, PcmCacheKey};
 use std::{
     collections::HashMap,
     fmt::{Debug, Formatter},
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc, Weak,
     },
     time::Duration,
@@ -40,6 +59,27 @@ pub(crate) struct VoiceInfo {
     pub(crate) name: String,
 }
 
+/// 语音引擎特有的参数
+#[derive(Debug, Clone)]
+pub(crate) struct EngineParam {
+    /// 参数名称，用于保存配置
+    pub(crate) name: String,
+    /// 朗读给用户的参数名称
+    pub(crate) label: String,
+    pub(crate) min: i32,
+    pub(crate) max: i32,
+    /// 每次调节的步长
+    pub(crate) step: i32,
+    pub(crate) default: i32,
+}
+
+impl EngineParam {
+    /// 把配置中保存的值限制在参数的取值范围内，配置文件中的值可能是手动修改的
+    pub(crate) fn clamp(&self, value: i32) -> i32 {
+        value.clamp(self.min, self.max.max(self.min))
+    }
+}
+
 /// TTS的属性枚举
 #[derive(Debug, Clone)]
 pub(crate) enum TtsProperty {
@@ -47,6 +87,7 @@ pub(crate) enum TtsProperty {
     Voice(VoiceInfo),
     Pitch(i32),
     Volume(i32),
+    Extra(EngineParam, i32),
 }
 
 /// 语音TTS的抽象接口
@@ -61,6 +102,47 @@ pub(crate) trait TtsEngine {
     async fn set_volume(&self, value: i32);
     async fn set_pitch(&self, value: i32);
     async fn set_voice(&self, id: String);
+
+    /// 获取引擎特有的参数，这些参数会加入到TTS属性的调节中
+    fn get_extra_params(&self) -> Vec<EngineParam> {
+        vec![]
+    }
+
+    /**
+    设置引擎特有参数的值。
+    `name` 参数名称。
+    `value` 参数的值。
+    */
+    async fn set_extra_param(&self, _name: &str, _value: i32) {}
+
+    /// 引擎是否可以直接朗读SSML
+    fn supports_ssml(&self) -> bool {
+        false
+    }
+
+    /**
+    朗读SSML，只有supports_ssml返回true的引擎才会收到。
+    `ssml` SSML文本。
+    */
+    async fn speak_ssml(&self, ssml: &str) {
+        self.speak(ssml).await
+    }
+
+    //noinspection StructuralWrap
+    /**
+    合成语音但不播放，返回PCM数据和格式，合成的语音可以放入缓存，以后通过play_pcm播放。不支持的引擎返回None。
+    `text` 要合成的文字。
+    */
+    async fn synth(&self, _text: &str) -> Option<(Vec<u8>, PcmFormat)> {
+        None
+    }
+
+    /**
+    播放之前合成的语音。
+    `data` PCM数据。
+    `format` 音频的格式。
+    */
+    async fn play_pcm(&self, _data: &[u8], _format: PcmFormat) {}
 }
 
 /// 移动TTS属性的方向
@@ -83,6 +165,11 @@ pub(crate) struct Tts {
     is_cancelled: AtomicBool,
     all_engines: RwLock<HashMap<String, Arc<dyn TtsEngine + Sync + Send>>>,
     all_voices: ArcSwapAny<Arc<Vec<VoiceInfo>>>,
+    // 编译好的语音词典，词典的配置改变后清空，下次使用时重新编译
+    speech_dict: Mutex<Option<Arc<SpeechDict>>>,
+    pcm_cache: Mutex<PcmCache>,
+    // 超过这个字符数量的文字会分段合成和朗读，0表示不分段
+    segment_length: AtomicUsize,
     context: Weak<Context>,
 }
 
@@ -94,6 +181,9 @@ impl Tts {
             is_cancelled: false.into(),
             all_engines: HashMap::new().into(),
             all_voices: ArcSwapAny::from(Arc::new(Vec::new())),
+            speech_dict: Default::default(),
+            pcm_cache: Default::default(),
+            segment_length: AtomicUsize::new(0),
             context,
         }
     }
@@ -102,32 +192,341 @@ impl Tts {
     /**
     朗读文字，如果当前有朗读的任务，则进行排队。
     本方法会等待朗读完毕，如果朗读成功，则返回true；如果中途通过stop函数停止，或者朗读失败，则返回false。
+    朗读前需要调用reset清除停止的状态，语音调度器在取出语音时会完成这一步。
     `text` 需要朗读的文本。
     */
     pub(crate) async fn speak(&self, text: String) -> bool {
         assert!(text.len() > 0);
 
+        let config = self.context.get_config_manager().get_config().tts_config;
+        // 单个大写字母（逐字朗读、缓冲区浏览和键入回显）按照设置的方式提示
+        if is_single_capital(&text) {
+            let capital = &config.capital;
+            if capital.play_sound {
+                let context = self.context.clone();
+                self.context.get_work_runtime().spawn(async move {
+                    let arg = SoundArgument::Single(SoundEvent::Capital);
+                    context.get_performer().play_sound(arg).await;
+                });
+            }
+            if capital.pitch_change != 0 || capital.say_prefix {
+                let prefix = t!("tts.capital_prefix");
+                let nodes = mark_capitals(&text, capital, config.pitch, &prefix);
+                return self.speak_markup(&SpeechMarkup { nodes }).await;
+            }
+        }
+
+        let text = self.prepare_text(&text, &config);
+        if text.is_empty() {
+            return true;
+        }
+
+        if !config.language_voices.is_empty() {
+            return self.speak_by_language(&text, &config).await;
+        }
         if let Some(engine) = self.get_engine().await.upgrade() {
-            // 这里Chars是迭代器，没有计算，不损耗性能，确保text不为空,unwrap不会失败
-            let mut chars = text.chars();
-            let first_char = chars.next().unwrap();
-            match chars.next() {
-                Some(_) => engine.speak(&text).await,
-
-                None => {
-                    let text = transform_single_char(&first_char);
-                    engine.speak(&text).await;
+            let params = (config.speed, config.pitch, config.volume);
+            return self.speak_on(&engine, &text, &config.voice.1, params).await;
+        }
+
+        !self.is_cancelled.load(Ordering::Acquire)
+    }
+
+    //noinspection StructuralWrap
+    /**
+    朗读SSML，如果格式不正确，则不朗读并返回false。
+    本方法会等待朗读完毕，如果朗读成功，则返回true；如果中途通过stop函数停止，或者朗读失败，则返回false。
+    `ssml` SSML文本。
+    */
+    pub(crate) async fn speak_ssml(&self, ssml: &str) -> bool {
+        match SpeechMarkup::parse(ssml) {
+            Ok(markup) => self.speak_markup(&markup).await,
+            Err(e) => {
+                error!("Invalid ssml `{}`. {}", ssml, e);
+                false
+            }
+        }
+    }
+
+    //noinspection StructuralWrap
+    /**
+    朗读语音标记，支持SSML的引擎直接接收SSML，其他引擎按照标记分段朗读，并在段落之间调整语音参数。
+    本方法会等待朗读完毕，如果朗读成功，则返回true；如果中途通过stop函数停止，或者朗读失败，则返回false。
+    `markup` 语音标记。
+    */
+    pub(crate) async fn speak_markup(&self, markup: &SpeechMarkup) -> bool {
+        let Some(engine) = self.get_engine().await.upgrade() else {
+            return false;
+        };
+
+        let config = self.context.get_config_manager().get_config().tts_config;
+        // 逐字朗读的文字中的每一个大写字母也需要提示
+        let marked;
+        let markup = if config.capital.in_spelled_words {
+            let prefix = t!("tts.capital_prefix");
+            let nodes =
+                mark_spelled_capitals(&markup.nodes, &config.capital, config.pitch, &prefix);
+            marked = SpeechMarkup { nodes };
+            &marked
+        } else {
+            markup
+        };
+
+        if engine.supports_ssml() {
+            // 文字节点和分段朗读一样需要应用语音词典、数字和符号的朗读规则
+            let markup = markup.map_text(&|t| self.prepare_text(t, &config));
+            engine.speak_ssml(&markup.to_ssml()).await;
+            engine.wait().await;
+            return !self.is_cancelled.load(Ordering::Acquire);
+        }
+
+        // 修改过引擎的参数以后需要恢复
+        let mut changed = false;
+        let mut result = true;
+        for segment in markup.flatten(config.speed, config.pitch, config.volume) {
+            if self.is_cancelled.load(Ordering::Acquire) {
+                result = false;
+                break;
+            }
+
+            let (text, speed, pitch, volume, voice) = match segment {
+                SpeechSegment::Break(duration) => {
+                    sleep(duration).await;
+                    continue;
                 }
+                SpeechSegment::Text {
+                    text,
+                    speed,
+                    pitch,
+                    volume,
+                    voice,
+                } => (text, speed, pitch, volume, voice),
+            };
+            let text = self.prepare_text(&text, &config);
+            if text.trim().is_empty() {
+                continue;
             }
 
-            {
-                self.is_cancelled.store(false, Ordering::Release);
+            let voice = voice.and_then(|v| {
+                self.all_voices
+                    .load()
+                    .iter()
+                    .find(|i| i.id == v || i.name == v)
+                    .cloned()
+            });
+            let found = voice
+                .as_ref()
+                .and_then(|v| self.all_engines.read().get(&v.engine).cloned());
+            let (engine, voice) = match (found, voice) {
+                (Some(x), Some(v)) => {
+                    x.set_voice(v.id.clone()).await;
+                    changed = true;
+                    (x, v.id)
+                }
+                _ => {
+                    // 恢复被前面的片段修改过的语音
+                    if changed && engine.get_name() == config.voice.0 {
+                        engine.set_voice(config.voice.1.clone()).await;
+                    }
+                    (engine.clone(), config.voice.1.clone())
+                }
+            };
+            if changed || (speed, pitch, volume) != (config.speed, config.pitch, config.volume) {
+                engine.set_speed(speed).await;
+                engine.set_pitch(pitch).await;
+                engine.set_volume(volume).await;
+                changed = true;
             }
 
-            engine.wait().await;
+            let params = (speed, pitch, volume);
+            if !self.speak_on(&engine, &text, &voice, params).await {
+                result = false;
+                break;
+            }
+        }
+
+        if changed {
+            self.apply_config(&config).await;
+        }
+
+        result
+    }
+
+    // 朗读之前处理文字，依次应用语音词典、数字和符号的朗读规则，单个字符会转换成他的读法
+    fn prepare_text(&self, text: &str, config: &TtsConfig) -> String {
+        let text = self.apply_speech_dict(text);
+
+        let mut chars = text.chars();
+        let Some(first_char) = chars.next() else {
+            return text;
+        };
+        match chars.next() {
+            Some(_) => {
+                let text = normalize_numbers(&text, config.number_mode);
+                process_symbols(&text, config.symbol_level)
+            }
+            None => transform_single_char(&first_char),
+        }
+    }
+
+    // 使用指定的引擎朗读文字并等待完毕，如果中途被停止，则返回false
+    // 短文字优先使用缓存的语音；长文字分段合成，播放一段的同时合成下一段，这样第一段语音可以尽快开始播放，
+    // 每一段之间都检查是否已经停止，停止时不需要等待整段文字合成完毕
+    async fn speak_on(
+        &self,
+        engine: &Arc<dyn TtsEngine + Sync + Send>,
+        text: &str,
+        voice: &str,
+        (speed, pitch, volume): (i32, i32, i32),
+    ) -> bool {
+        let key = |text: &str| PcmCacheKey {
+            engine: engine.get_name(),
+            voice: voice.to_string(),
+            speed,
+            pitch,
+            volume,
+            text: text.to_string(),
+        };
+
+        let max_len = self.segment_length.load(Ordering::Acquire);
+        let chars = text.chars().collect::<Vec<_>>();
+        if max_len == 0 || chars.len() <= max_len {
+            let pcm = self.get_pcm(engine, key(text), false).await;
+            // 合成的过程中可能已经停止了，这时不能再播放
+            if self.is_cancelled.load(Ordering::Acquire) {
+                return false;
+            }
+            match pcm {
+                Some((data, format)) => engine.play_pcm(&data, format).await,
+                None => engine.speak(text).await,
+            }
+        } else {
+            for range in split_segments(&chars, max_len) {
+                if self.is_cancelled.load(Ordering::Acquire) {
+                    return false;
+                }
+                let segment = chars[range].iter().collect::<String>();
+                let pcm = self.get_pcm(engine, key(&segment), true).await;
+                if self.is_cancelled.load(Ordering::Acquire) {
+                    return false;
+                }
+                match pcm {
+                    Some((data, format)) => engine.play_pcm(&data, format).await,
+                    // 不支持合成的引擎只能一段一段地朗读
+                    None => {
+                        engine.speak(&segment).await;
+                        engine.wait().await;
+                    }
+                }
+            }
+        }
+
+        engine.wait().await;
+        !self.is_cancelled.load(Ordering::Acquire)
+    }
+
+    // 获取文字的语音，优先使用缓存，合成的短文字会放入缓存
+    // `force` 为false时只合成可以缓存的文字，引擎不支持合成时返回None
+    async fn get_pcm(
+        &self,
+        engine: &Arc<dyn TtsEngine + Sync + Send>,
+        key: PcmCacheKey,
+        force: bool,
+    ) -> Option<(Arc<Vec<u8>>, PcmFormat)> {
+        let (cached, accepts) = {
+            let mut cache = self.pcm_cache.lock();
+            (cache.get(&key), cache.accepts(&key.text))
+        };
+        if cached.is_some() || !(accepts || force) {
+            return cached;
+        }
+
+        let (data, format) = engine.synth(&key.text).await?;
+        let data = Arc::new(data);
+        self.pcm_cache.lock().put(key, data.clone(), format);
+        Some((data, format))
+    }
+
+    // 按照书写系统把文字分段，每一段使用对应语言的语音朗读，没有配置语音的段落使用当前语音
+    async fn speak_by_language(&self, text: &str, config: &TtsConfig) -> bool {
+        // 相邻的、使用同一个语音的段落合并在一起朗读
+        let mut runs: Vec<((String, String), String)> = vec![];
+        for (script, run) in segment_by_script(text) {
+            let voice = script
+                .and_then(|s| config.language_voices.get(&s))
+                .filter(|(engine, _)| self.all_engines.read().contains_key(engine))
+                .unwrap_or(&config.voice);
+            match runs.last_mut() {
+                Some((last, text)) if last == voice => text.push_str(&run),
+                _ => runs.push((voice.clone(), run)),
+            }
         }
 
-        return !self.is_cancelled.load(Ordering::Acquire);
+        // 记录每个引擎当前使用的语音，避免重复设置
+        let mut current = HashMap::from([config.voice.clone()]);
+        let mut result = true;
+        for (i, ((engine_name, id), run)) in runs.into_iter().enumerate() {
+            if i > 0 && self.is_cancelled.load(Ordering::Acquire) {
+                result = false;
+                break;
+            }
+
+            let found = self.all_engines.read().get(&engine_name).cloned();
+            let (engine, voice) = match found {
+                Some(x) => {
+                    if current.get(&engine_name) != Some(&id) {
+                        x.set_voice(id.clone()).await;
+                        current.insert(engine_name, id.clone());
+                    }
+                    (x, id)
+                }
+                None => match self.get_engine().await.upgrade() {
+                    Some(x) => (x, config.voice.1.clone()),
+                    None => continue,
+                },
+            };
+            let params = (config.speed, config.pitch, config.volume);
+            if !self.speak_on(&engine, &run, &voice, params).await {
+                result = false;
+                break;
+            }
+        }
+
+        // 恢复当前语音
+        let (engine_name, id) = &config.voice;
+        if current.get(engine_name) != Some(id) {
+            let found = self.all_engines.read().get(engine_name).cloned();
+            if let Some(x) = found {
+                x.set_voice(id.clone()).await;
+            }
+        }
+
+        result
+    }
+
+    /**
+    清空编译好的语音词典，词典的配置改变后调用，下次朗读时重新编译。
+    */
+    pub(crate) fn clear_speech_dict(&self) {
+        *self.speech_dict.lock() = None;
+    }
+
+    // 应用语音词典，只在第一次使用或者词典的配置改变后编译词典
+    fn apply_speech_dict(&self, text: &str) -> String {
+        let dict = self
+            .speech_dict
+            .lock()
+            .get_or_insert_with(|| {
+                let config = self.context.get_config_manager().get_config();
+                Arc::new(SpeechDict::build(&config.speech_dict_config.entries))
+            })
+            .clone();
+
+        let app = match dict.has_scoped_rules() {
+            true => self.context.get_config_manager().get_app(),
+            false => None,
+        };
+        dict.apply(text, app.as_deref())
     }
 
     async fn get_engine(&self) -> Weak<dyn TtsEngine + Sync + Send> {
@@ -151,35 +550,16 @@ impl Tts {
     }
 
     /**
-    停止当前的朗读任务。
+    清除停止的状态，此后的朗读不会因为之前的stop_all而中断。
     */
-    pub(crate) async fn stop(&self) {
-        self.is_cancelled.store(true, Ordering::Release);
-        let engine = self
-            .context
-            .get_config_manager()
-            .get_config()
-            .tts_config
-            .voice
-            .0
-            .clone();
-
-        let lock = self.all_engines.read();
-        if let Some(x) = lock.get(&engine) {
-            x.stop();
-            return;
-        };
-        if let Some(default_engine) = self.default_engine.get() {
-            if let Some(x) = lock.get(default_engine) {
-                x.stop();
-            };
-        }
+    pub(crate) fn reset(&self) {
+        self.is_cancelled.store(false, Ordering::Release);
     }
 
     /**
     停止所有语音引擎的朗读。
     */
-    pub(crate) async fn stop_all(&self) {
+    pub(crate) fn stop_all(&self) {
         self.is_cancelled.store(true, Ordering::Release);
         self.all_engines.read().iter().for_each(|(_, engine)| {
             engine.stop();
@@ -201,6 +581,7 @@ impl Tts {
             volume,
             voice: (engine, id),
             item,
+            ..
         } = self
             .context
             .get_config_manager()
@@ -219,21 +600,36 @@ impl Tts {
             TtsPropertyItem::Pitch => cfg.pitch = set_val(pitch),
             TtsPropertyItem::Volume => cfg.volume = set_val(volume),
             TtsPropertyItem::Voice => {
-                self.stop_all().await;
+                self.stop_all();
                 let voice = self.switch_voice(engine, id, value_change).await;
                 cfg.voice = (voice.engine, voice.id)
             }
+            TtsPropertyItem::Extra(name) => match self.find_extra_param(&name).await {
+                Some((engine, param)) => {
+                    let values = cfg.engine_params.entry(engine).or_default();
+                    let value = param.clamp(values.get(&name).copied().unwrap_or(param.default));
+                    let value = match value_change {
+                        ValueChange::Increment => (value + param.step).min(param.max),
+                        ValueChange::Decrement => (value - param.step).max(param.min),
+                    };
+                    values.insert(name, value);
+                }
+                // 参数所属的引擎已经不存在了，回到语速
+                None => cfg.item = TtsPropertyItem::Speed,
+            },
         };
 
-        self.apply_config(&cfg).await;
-
+        // 引擎的参数通过订阅配置的修改应用
         let mut root = self.context.get_config_manager().get_config();
         root.tts_config = cfg;
         self.context.get_config_manager().set_config(&root);
     }
 
-    /// 获取当前TTS属性值
-    pub(crate) async fn get_tts_prop_value(&self, item: Option<TtsPropertyItem>) -> TtsProperty {
+    /// 获取当前TTS属性值，如果引擎的额外参数已经不存在，则返回None
+    pub(crate) async fn get_tts_prop_value(
+        &self,
+        item: Option<TtsPropertyItem>,
+    ) -> Option<TtsProperty> {
         let config = self
             .context
             .get_config_manager()
@@ -241,7 +637,7 @@ impl Tts {
             .tts_config
             .clone();
 
-        match item.map(|x| x).unwrap_or(config.item) {
+        let property = match item.map(|x| x).unwrap_or(config.item) {
             TtsPropertyItem::Speed => TtsProperty::Speed(config.speed),
             TtsPropertyItem::Pitch => TtsProperty::Pitch(config.pitch),
             TtsPropertyItem::Volume => TtsProperty::Volume(config.volume),
@@ -254,7 +650,19 @@ impl Tts {
                     None => TtsProperty::Voice(all_voices.first().unwrap().clone()),
                 }
             }
-        }
+            TtsPropertyItem::Extra(name) => {
+                let (engine, param) = self.find_extra_param(&name).await?;
+                let value = config
+                    .engine_params
+                    .get(&engine)
+                    .and_then(|v| v.get(&name))
+                    .copied()
+                    .map_or(param.default, |v| param.clamp(v));
+                TtsProperty::Extra(param, value)
+            }
+        };
+
+        Some(property)
     }
 
     /**
@@ -308,6 +716,11 @@ impl Tts {
 
     // 应用配置到TTS
     pub(crate) async fn apply_config(&self, config: &TtsConfig) {
+        // 语音参数改变后，缓存的语音全部失效
+        self.pcm_cache.lock().apply(config);
+        self.segment_length
+            .store(config.segment_length, Ordering::Release);
+
         for (_, engine) in { self.all_engines.read().clone() }.iter() {
             let (engine_name, id) = config.voice.clone();
             if engine.get_name() == engine_name {
@@ -317,6 +730,21 @@ impl Tts {
             engine.set_speed(config.speed).await;
             engine.set_volume(config.volume).await;
             engine.set_pitch(config.pitch).await;
+
+            // 只设置保存过的特有参数，其他参数保持语音本身的设定
+            let values = config.engine_params.get(&engine.get_name());
+            for param in engine.get_extra_params() {
+                if let Some(value) = values.and_then(|v| v.get(&param.name)) {
+                    let clamped = param.clamp(*value);
+                    if clamped != *value {
+                        warn!(
+                            "The value {} of the param `{}` is out of range, changed to {}.",
+                            value, param.name, clamped
+                        );
+                    }
+                    engine.set_extra_param(&param.name, clamped).await;
+                }
+            }
         }
     }
 
@@ -327,28 +755,130 @@ impl Tts {
             .get_config()
             .tts_config
             .clone();
-        cfg.item = match direction {
-            Direction::Next => match cfg.item {
-                TtsPropertyItem::Speed => TtsPropertyItem::Pitch,
-                TtsPropertyItem::Pitch => TtsPropertyItem::Volume,
-                TtsPropertyItem::Volume => TtsPropertyItem::Voice,
-                TtsPropertyItem::Voice => TtsPropertyItem::Speed,
-            },
-            Direction::Prev => match cfg.item {
-                TtsPropertyItem::Speed => TtsPropertyItem::Voice,
-                TtsPropertyItem::Pitch => TtsPropertyItem::Speed,
-                TtsPropertyItem::Volume => TtsPropertyItem::Pitch,
-                TtsPropertyItem::Voice => TtsPropertyItem::Volume,
-            },
+        let items = self.get_prop_items().await;
+        cfg.item = match (items.iter().position(|i| *i == cfg.item), direction) {
+            (Some(i), Direction::Next) => items[(i + 1) % items.len()].clone(),
+            (Some(i), Direction::Prev) => items[(i + items.len() - 1) % items.len()].clone(),
+            // 切换引擎以后，原来引擎特有的参数已经不存在了
+            (None, _) => TtsPropertyItem::Speed,
         };
 
-        self.apply_config(&cfg).await;
-
+        // 只切换了调节的属性，不需要应用到引擎
         let mut root = self.context.get_config_manager().get_config();
         root.tts_config = cfg;
         self.context.get_config_manager().set_config(&root);
     }
 
+    //noinspection StructuralWrap
+    /**
+    切换到上一个或下一个语音方案，一次性应用方案中的所有语音设置。
+    返回切换后的方案名称，如果没有任何方案，则返回None。
+    `direction` 切换的方向。
+    */
+    pub(crate) async fn switch_voice_profile(&self, direction: Direction) -> Option<String> {
+        let mut root = self.context.get_config_manager().get_config();
+        let profiles = &root.voice_profiles_config.profiles;
+        if profiles.is_empty() {
+            return None;
+        }
+
+        let len = profiles.len();
+        let current = root.voice_profiles_config.current.as_ref();
+        let position = profiles.iter().position(|p| Some(&p.name) == current);
+        let index = match (position, direction) {
+            (Some(i), Direction::Next) => (i + 1) % len,
+            (Some(i), Direction::Prev) => (i + len - 1) % len,
+            (None, Direction::Next) => 0,
+            (None, Direction::Prev) => len - 1,
+        };
+        let VoiceProfile {
+            name,
+            mut tts_config,
+        } = profiles[index].clone();
+        // 外部命令引擎只在启动时加载，不随方案切换
+        tts_config.command_engines = root.tts_config.command_engines.clone();
+
+        // 方案中的语音设置通过订阅配置的修改应用到引擎
+        root.tts_config = tts_config;
+        root.voice_profiles_config.current = Some(name.clone());
+        self.context.get_config_manager().set_config(&root);
+
+        Some(name)
+    }
+
+    //noinspection StructuralWrap
+    /**
+    把当前的语音设置保存到语音方案，返回方案名称。
+    `as_new` 是否保存成一个新的方案，否则保存到当前方案（还没有方案时也会新建一个）。
+    */
+    pub(crate) fn save_voice_profile(&self, as_new: bool) -> String {
+        let mut root = self.context.get_config_manager().get_config();
+        let tts_config = root.tts_config.clone();
+        let cfg = &mut root.voice_profiles_config;
+
+        let current = cfg
+            .current
+            .clone()
+            .filter(|n| !as_new && cfg.profiles.iter().any(|p| &p.name == n));
+        let name = match current {
+            Some(name) => name,
+            None => {
+                // 新方案的名称不能和已有的方案重复
+                let mut index = cfg.profiles.len() + 1;
+                loop {
+                    let name = t!("tts.profile_new_name", value = index).to_string();
+                    if !cfg.profiles.iter().any(|p| p.name == name) {
+                        break name;
+                    }
+                    index += 1;
+                }
+            }
+        };
+
+        match cfg.profiles.iter_mut().find(|p| p.name == name) {
+            Some(p) => p.tts_config = tts_config,
+            None => cfg.profiles.push(VoiceProfile {
+                name: name.clone(),
+                tts_config,
+            }),
+        }
+        cfg.current = Some(name.clone());
+        self.context.get_config_manager().set_config(&root);
+
+        name
+    }
+
+    // 当前引擎可以调节的所有属性，依次是语速、音调、音量、语音和引擎特有的参数
+    async fn get_prop_items(&self) -> Vec<TtsPropertyItem> {
+        let mut items = vec![
+            TtsPropertyItem::Speed,
+            TtsPropertyItem::Pitch,
+            TtsPropertyItem::Volume,
+            TtsPropertyItem::Voice,
+        ];
+        if let Some(engine) = self.get_engine().await.upgrade() {
+            items.extend(
+                engine
+                    .get_extra_params()
+                    .into_iter()
+                    .map(|p| TtsPropertyItem::Extra(p.name)),
+            );
+        }
+
+        items
+    }
+
+    // 查找当前引擎特有的参数，返回（引擎名称，参数）
+    async fn find_extra_param(&self, name: &str) -> Option<(String, EngineParam)> {
+        let engine = self.get_engine().await.upgrade()?;
+        let param = engine
+            .get_extra_params()
+            .into_iter()
+            .find(|p| p.name == name)?;
+
+        Some((engine.get_name(), param))
+    }
+
     async fn switch_voice(
         &self,
         engine: String,
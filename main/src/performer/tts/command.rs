@@ -0,0 +1,445 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::{
+    configs::items::tts::{
+        CommandArgTemplate, CommandEngineConfig, CommandOutputFormat, CommandTextInput,
+    },
+    performer::{
+        audio::{AudioSink, PcmFormat},
+        tts::TtsEngine,
+    },
+};
+use log::error;
+use parking_lot::Mutex;
+use std::{process::Stdio, sync::Arc};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    process::Command,
+};
+
+// 引擎当前的属性值（读屏的取值范围是0到100）
+#[derive(Debug, Clone)]
+struct CommandParams {
+    speed: i32,
+    pitch: i32,
+    volume: i32,
+    voice: String,
+}
+
+//noinspection StructuralWrap
+/**
+ * 外部命令语音引擎，每次朗读时启动一个合成器进程（例如espeak-ng或者自定义的脚本），通过标准输入或参数传入文字，再从标准输出读取PCM或WAV数据。
+ * 这样不需要重新编译读屏就可以接入任意的离线合成器。
+ * */
+#[derive(Debug)]
+pub(crate) struct CommandEngine {
+    config: CommandEngineConfig,
+    params: Mutex<CommandParams>,
+    output_stream: Arc<dyn AudioSink>,
+}
+
+impl CommandEngine {
+    /**
+     * 创建外部命令语音引擎。
+     * `config` 引擎的配置。
+     * `output_stream` 音频的输出端。
+     * */
+    pub(crate) fn new(config: CommandEngineConfig, output_stream: Arc<dyn AudioSink>) -> Self {
+        let params = CommandParams {
+            speed: 50,
+            pitch: 50,
+            volume: 100,
+            voice: config
+                .voices
+                .first()
+                .map(|(id, _)| id.clone())
+                .unwrap_or_default(),
+        };
+        Self {
+            output_stream,
+            params: params.into(),
+            config,
+        }
+    }
+
+    /// 合成器输出的音频格式
+    pub(crate) fn get_format(&self) -> PcmFormat {
+        PcmFormat::new(self.config.sample_rate, self.config.channels)
+    }
+
+    // 生成合成器的参数，extra_args会放在其他参数的前面
+    fn make_args(&self, text: &str, extra_args: &[String]) -> Vec<String> {
+        let mut args = extra_args.to_vec();
+        let params = self.params.lock();
+        args.extend(build_args(&self.config, &params, text));
+        args
+    }
+
+    // 运行合成器并播放合成的语音
+    async fn run(&self, text: &str, extra_args: &[String]) {
+        self.output_stream.start();
+
+        let args = self.make_args(text, extra_args);
+        let format = self.get_format();
+        let res = synth(&self.config, args, text, |data| {
+            if self.output_stream.is_stopped() {
+                return false;
+            }
+            self.output_stream.put_pcm(data, format);
+            true
+        })
+        .await;
+        if let Err(e) = res {
+            error!(
+                "Can't run the speech synthesizer `{}`. {}",
+                self.config.program, e
+            );
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TtsEngine for CommandEngine {
+    async fn speak(&self, text: &str) {
+        self.run(text, &[]).await
+    }
+
+    async fn synth(&self, text: &str) -> Option<(Vec<u8>, PcmFormat)> {
+        let mut result = vec![];
+        let res = synth(&self.config, self.make_args(text, &[]), text, |data| {
+            result.extend_from_slice(data);
+            true
+        })
+        .await;
+        match res {
+            Ok(_) => Some((result, self.get_format())),
+            Err(e) => {
+                error!(
+                    "Can't run the speech synthesizer `{}`. {}",
+                    self.config.program, e
+                );
+                None
+            }
+        }
+    }
+
+    async fn play_pcm(&self, data: &[u8], format: PcmFormat) {
+        self.output_stream.start();
+        self.output_stream.put_pcm(data, format);
+    }
+
+    async fn wait(&self) {
+        self.output_stream.wait().await;
+    }
+
+    fn stop(&self) {
+        self.output_stream.stop();
+    }
+
+    fn get_name(&self) -> String {
+        self.config.name.clone()
+    }
+
+    async fn get_all_voices(&self) -> Vec<(String, String)> {
+        self.config.voices.clone()
+    }
+
+    async fn set_speed(&self, value: i32) {
+        self.params.lock().speed = value;
+    }
+
+    async fn set_volume(&self, value: i32) {
+        self.params.lock().volume = value;
+    }
+
+    async fn set_pitch(&self, value: i32) {
+        self.params.lock().pitch = value;
+    }
+
+    async fn set_voice(&self, id: String) {
+        self.params.lock().voice = id;
+    }
+
+    fn supports_ssml(&self) -> bool {
+        self.config.ssml_args.is_some()
+    }
+
+    async fn speak_ssml(&self, ssml: &str) {
+        self.run(ssml, self.config.ssml_args.as_deref().unwrap_or_default())
+            .await
+    }
+}
+
+// 把读屏的属性值（0到100）换算成命令参数的值
+fn convert_value(template: &Option<CommandArgTemplate>, value: i32) -> i32 {
+    match template {
+        Some(t) => t.range.0 + (t.range.1 - t.range.0) * value / 100,
+        None => value,
+    }
+}
+
+// 生成命令的参数列表，属性的参数模板排在固定参数的前面
+fn build_args(config: &CommandEngineConfig, params: &CommandParams, text: &str) -> Vec<String> {
+    let rate = convert_value(&config.rate, params.speed).to_string();
+    let pitch = convert_value(&config.pitch, params.pitch).to_string();
+    let volume = convert_value(&config.volume, params.volume).to_string();
+    let mut args = vec![];
+
+    for (template, value) in [
+        (&config.rate, &rate),
+        (&config.pitch, &pitch),
+        (&config.volume, &volume),
+    ] {
+        if let Some(t) = template {
+            args.extend(t.args.iter().map(|a| a.replace("{value}", value)));
+        }
+    }
+    if !params.voice.is_empty() {
+        args.extend(
+            config
+                .voice_args
+                .iter()
+                .map(|a| a.replace("{value}", &params.voice)),
+        );
+    }
+    args.extend(config.args.iter().map(|a| {
+        let a = a
+            .replace("{rate}", &rate)
+            .replace("{pitch}", &pitch)
+            .replace("{volume}", &volume)
+            .replace("{voice}", &params.voice);
+        match config.text_input {
+            CommandTextInput::Argument => a.replace("{text}", text),
+            CommandTextInput::Stdin => a,
+        }
+    }));
+
+    args
+}
+
+// 查找WAV数据中音频数据开始的位置，文件头还不完整时返回None，不是WAV格式时按照原始PCM数据处理
+fn find_wav_data(data: &[u8]) -> Option<usize> {
+    if data.len() < 12 {
+        return None;
+    }
+    if &data[..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Some(0);
+    }
+
+    let mut pos = 12;
+    while pos + 8 <= data.len() {
+        let size = u32::from_le_bytes([data[pos + 4], data[pos + 5], data[pos + 6], data[pos + 7]])
+            as usize;
+        if &data[pos..pos + 4] == b"data" {
+            return Some(pos + 8);
+        }
+        // 块的大小是奇数时后面有一个填充字节
+        pos += 8 + size + (size & 1);
+    }
+
+    None
+}
+
+//noinspection StructuralWrap
+/**
+ * 运行外部命令合成语音，每读取到一段PCM数据就调用一次回调函数，回调函数返回false时结束合成进程。
+ * 传给回调函数的数据总是完整的采样（16位）。
+ * `config` 引擎的配置。
+ * `args` 命令的参数列表。
+ * `text` 要合成的文字。
+ * `on_data` 接收PCM数据的回调函数。
+ * */
+async fn synth<F>(
+    config: &CommandEngineConfig,
+    args: Vec<String>,
+    text: &str,
+    mut on_data: F,
+) -> std::io::Result<()>
+where
+    F: FnMut(&[u8]) -> bool,
+{
+    let mut command = Command::new(&config.program);
+    command
+        .args(args)
+        .stdin(match config.text_input {
+            CommandTextInput::Stdin => Stdio::piped(),
+            CommandTextInput::Argument => Stdio::null(),
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    #[cfg(windows)]
+    command.creation_flags(windows::Win32::System::Threading::CREATE_NO_WINDOW.0);
+
+    let mut child = command.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let text = text.to_string();
+        // 写完以后关闭标准输入，合成器才知道文字已经结束
+        tokio::spawn(async move { stdin.write_all(text.as_bytes()).await.unwrap_or(()) });
+    }
+    let Some(mut stdout) = child.stdout.take() else {
+        return Ok(());
+    };
+
+    let block = (config.channels.max(1) * 2) as usize;
+    let mut skip_header = config.output_format == CommandOutputFormat::Wav;
+    let mut pending = vec![];
+    let mut buf = [0u8; 4096];
+    loop {
+        let len = stdout.read(&mut buf).await?;
+        if len == 0 {
+            break;
+        }
+        pending.extend_from_slice(&buf[..len]);
+
+        if skip_header {
+            let Some(offset) = find_wav_data(&pending) else {
+                continue;
+            };
+            pending.drain(..offset);
+            skip_header = false;
+        }
+        let len = pending.len() - pending.len() % block;
+        if len == 0 {
+            continue;
+        }
+        if !on_data(&pending[..len]) {
+            // 丢弃child的时候会结束合成进程
+            return Ok(());
+        }
+        pending.drain(..len);
+    }
+    child.wait().await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::performer::audio::MemorySink;
+
+    fn config(program: &str, args: &[&str], text_input: CommandTextInput) -> CommandEngineConfig {
+        CommandEngineConfig {
+            name: "Stub".to_string(),
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            text_input,
+            output_format: CommandOutputFormat::Pcm,
+            sample_rate: 22050,
+            channels: 1,
+            rate: None,
+            pitch: None,
+            volume: None,
+            voice_args: vec![],
+            voices: vec![],
+            ssml_args: None,
+        }
+    }
+
+    async fn run(config: &CommandEngineConfig, text: &str) -> Vec<u8> {
+        let params = CommandParams {
+            speed: 50,
+            pitch: 50,
+            volume: 100,
+            voice: String::new(),
+        };
+        let mut result = vec![];
+        synth(config, build_args(config, &params, text), text, |data| {
+            result.extend_from_slice(data);
+            true
+        })
+        .await
+        .unwrap();
+        result
+    }
+
+    #[test]
+    fn test_build_args() {
+        let mut cfg = config(
+            "espeak-ng",
+            &["--stdout", "{text}"],
+            CommandTextInput::Argument,
+        );
+        cfg.rate = Some(CommandArgTemplate {
+            args: vec!["-s".to_string(), "{value}".to_string()],
+            range: (80, 450),
+        });
+        cfg.voice_args = vec!["-v".to_string(), "{value}".to_string()];
+        let params = CommandParams {
+            speed: 50,
+            pitch: 50,
+            volume: 100,
+            voice: "zh".to_string(),
+        };
+        assert_eq!(
+            build_args(&cfg, &params, "你好"),
+            vec!["-s", "265", "-v", "zh", "--stdout", "你好"]
+        );
+    }
+
+    #[test]
+    fn test_find_wav_data() {
+        let mut wav = b"RIFF\0\0\0\0WAVEfmt \x10\0\0\0".to_vec();
+        wav.extend_from_slice(&[0; 16]);
+        assert_eq!(find_wav_data(&wav), None);
+        wav.extend_from_slice(b"data\x04\0\0\0\x01\x02\x03\x04");
+        assert_eq!(find_wav_data(&wav), Some(44));
+        assert_eq!(find_wav_data(b"raw pcm data"), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_synth_with_stub() {
+        let cfg = if cfg!(windows) {
+            config("cmd", &["/C", "echo {text}"], CommandTextInput::Argument)
+        } else {
+            config(
+                "sh",
+                &["-c", "printf %s \"$0\"", "{text}"],
+                CommandTextInput::Argument,
+            )
+        };
+        assert!(run(&cfg, "hello!").await.starts_with(b"hello!"));
+
+        let cfg = if cfg!(windows) {
+            config("cmd", &["/C", "more"], CommandTextInput::Stdin)
+        } else {
+            config("cat", &[], CommandTextInput::Stdin)
+        };
+        assert!(run(&cfg, "stdin!").await.starts_with(b"stdin!"));
+    }
+
+    #[tokio::test]
+    async fn test_speak_to_sink() {
+        let cfg = if cfg!(windows) {
+            config("cmd", &["/C", "more"], CommandTextInput::Stdin)
+        } else {
+            config("cat", &[], CommandTextInput::Stdin)
+        };
+        let sink = Arc::new(MemorySink::default());
+        let engine = CommandEngine::new(cfg, sink.clone());
+        engine.speak("speak!").await;
+        engine.wait().await;
+
+        let (format, data) = sink.get_data();
+        assert_eq!(format, Some(PcmFormat::new(22050, 1)));
+        assert!(data.starts_with(b"speak!"));
+
+        // 合成但不播放，再播放合成的语音
+        let (data, format) = engine.synth("synth!").await.unwrap();
+        assert!(data.starts_with(b"synth!"));
+        engine.play_pcm(&data, format).await;
+        assert_eq!(sink.get_data(), (Some(format), data));
+    }
+}
@@ -0,0 +1,235 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::{
+    configs::items::tts::{PcmCacheConfig, TtsConfig},
+    performer::audio::PcmFormat,
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// 语音缓存的键，同样的文字使用不同的语音或参数合成的结果是不同的
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct PcmCacheKey {
+    pub(crate) engine: String,
+    pub(crate) voice: String,
+    pub(crate) speed: i32,
+    pub(crate) pitch: i32,
+    pub(crate) volume: i32,
+    pub(crate) text: String,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    data: Arc<Vec<u8>>,
+    format: PcmFormat,
+    // 最后一次使用的序号，序号最小的最先被淘汰
+    last_used: u64,
+}
+
+//noinspection StructuralWrap
+/**
+ * 合成语音的缓存，保存短文字（例如角色名称、“空格”和“逗号”）合成的PCM数据。
+ * 缓存占用的内存超过限制时，淘汰最久没有使用的语音；语音参数改变后，所有缓存的语音都会失效。
+ * */
+#[derive(Debug, Default)]
+pub(crate) struct PcmCache {
+    config: PcmCacheConfig,
+    // 缓存的语音是使用哪个配置合成的
+    applied: Option<TtsConfig>,
+    entries: HashMap<PcmCacheKey, CacheEntry>,
+    size: usize,
+    counter: u64,
+}
+
+impl PcmCache {
+    //noinspection StructuralWrap
+    /**
+     * 应用语音配置，如果语音或参数（包括引擎特有的参数）改变了，则清空缓存。
+     * `config` 语音的配置。
+     * */
+    pub(crate) fn apply(&mut self, config: &TtsConfig) {
+        let changed = match &self.applied {
+            Some(x) => {
+                x.voice != config.voice
+                    || x.speed != config.speed
+                    || x.pitch != config.pitch
+                    || x.volume != config.volume
+                    || x.engine_params != config.engine_params
+            }
+            None => true,
+        };
+        if changed || !config.pcm_cache.enabled {
+            self.clear();
+        }
+        self.config = config.pcm_cache.clone();
+        self.applied = Some(config.clone());
+        self.evict();
+    }
+
+    /**
+     * 判断文字是否可以缓存，只缓存比较短的文字。
+     * `text` 要朗读的文字。
+     * */
+    pub(crate) fn accepts(&self, text: &str) -> bool {
+        self.config.enabled
+            && !text.trim().is_empty()
+            && text.chars().count() <= self.config.max_chars
+    }
+
+    /**
+     * 获取缓存的语音，返回PCM数据和格式。
+     * `key` 缓存的键。
+     * */
+    pub(crate) fn get(&mut self, key: &PcmCacheKey) -> Option<(Arc<Vec<u8>>, PcmFormat)> {
+        self.counter += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.counter;
+        Some((entry.data.clone(), entry.format))
+    }
+
+    /**
+     * 把合成的语音放入缓存，超过内存限制的语音不会被缓存。
+     * `key` 缓存的键。
+     * `data` PCM数据。
+     * `format` 音频的格式。
+     * */
+    pub(crate) fn put(&mut self, key: PcmCacheKey, data: Arc<Vec<u8>>, format: PcmFormat) {
+        if !self.accepts(&key.text) || data.len() > self.max_size() {
+            return;
+        }
+        self.counter += 1;
+        self.size += data.len();
+        let entry = CacheEntry {
+            data,
+            format,
+            last_used: self.counter,
+        };
+        if let Some(old) = self.entries.insert(key, entry) {
+            self.size -= old.data.len();
+        }
+        self.evict();
+    }
+
+    /// 清空缓存
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.size = 0;
+    }
+
+    fn max_size(&self) -> usize {
+        self.config.max_memory * 1024
+    }
+
+    // 淘汰最久没有使用的语音，直到占用的内存不超过限制
+    fn evict(&mut self) {
+        while self.size > self.max_size() {
+            let Some(key) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.last_used)
+                .map(|(k, _)| k.clone())
+            else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&key) {
+                self.size -= entry.data.len();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configs::items::tts::NumberMode;
+
+    fn key(text: &str) -> PcmCacheKey {
+        PcmCacheKey {
+            engine: "Stub".to_string(),
+            voice: "voice".to_string(),
+            speed: 50,
+            pitch: 50,
+            volume: 100,
+            text: text.to_string(),
+        }
+    }
+
+    fn cache(max_memory: usize) -> PcmCache {
+        let mut config = TtsConfig::default();
+        config.pcm_cache.max_memory = max_memory;
+        let mut cache = PcmCache::default();
+        cache.apply(&config);
+        cache
+    }
+
+    #[test]
+    fn test_get_and_put() {
+        let format = PcmFormat::new(16000, 1);
+        let mut cache = cache(1);
+        assert!(cache.accepts("空格"));
+        assert!(!cache.accepts("这是一段比较长的文字"));
+        assert!(!cache.accepts(" "));
+
+        cache.put(key("空格"), Arc::new(vec![1; 100]), format);
+        assert_eq!(
+            cache.get(&key("空格")),
+            Some((Arc::new(vec![1; 100]), format))
+        );
+        assert_eq!(cache.get(&key("逗号")), None);
+        let mut other = key("空格");
+        other.speed = 60;
+        assert_eq!(cache.get(&other), None);
+
+        // 太长的文字和太大的数据不会被缓存
+        cache.put(key("这是一段比较长的文字"), Arc::new(vec![1; 100]), format);
+        cache.put(key("逗号"), Arc::new(vec![1; 2000]), format);
+        assert_eq!(cache.entries.len(), 1);
+    }
+
+    #[test]
+    fn test_evict() {
+        let format = PcmFormat::new(16000, 1);
+        let mut cache = cache(1);
+        cache.put(key("a"), Arc::new(vec![0; 400]), format);
+        cache.put(key("b"), Arc::new(vec![0; 400]), format);
+        // 使用过的语音会保留下来
+        assert!(cache.get(&key("a")).is_some());
+        cache.put(key("c"), Arc::new(vec![0; 400]), format);
+        assert!(cache.get(&key("a")).is_some());
+        assert!(cache.get(&key("b")).is_none());
+        assert!(cache.get(&key("c")).is_some());
+        assert_eq!(cache.size, 800);
+    }
+
+    #[test]
+    fn test_apply() {
+        let format = PcmFormat::new(16000, 1);
+        let mut config = TtsConfig::default();
+        let mut cache = PcmCache::default();
+        cache.apply(&config);
+        cache.put(key("a"), Arc::new(vec![0; 10]), format);
+
+        // 和语音无关的配置改变时，缓存仍然有效
+        config.number_mode = NumberMode::Full;
+        cache.apply(&config);
+        assert!(cache.get(&key("a")).is_some());
+
+        config.engine_params.insert(
+            "Stub".to_string(),
+            HashMap::from([("gender".to_string(), 1)]),
+        );
+        cache.apply(&config);
+        assert!(cache.get(&key("a")).is_none());
+        assert_eq!(cache.size, 0);
+    }
+}
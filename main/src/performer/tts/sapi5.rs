@@ -11,21 +11,31 @@
  * See the License for the specific language governing permissions and limitations under the License.
  */
 
-use crate::performer::tts::TtsEngine;
-use rigela_utils::bass::BassChannelOutputStream;
+use crate::performer::{
+    audio::{AudioSink, PcmFormat},
+    tts::TtsEngine,
+};
+use std::sync::Arc;
 use tokio::sync::mpsc::unbounded_channel;
 use win_wrap::tts::Sapi5TtsSynthesizer;
 
 #[derive(Debug)]
 pub(crate) struct Sapi5Engine {
-    output_stream: BassChannelOutputStream,
+    output_stream: Arc<dyn AudioSink>,
     synth: Sapi5TtsSynthesizer,
 }
 
 impl Sapi5Engine {
-    pub(crate) fn new() -> Self {
+    /// sapi5语音输出的音频格式
+    pub(crate) const FORMAT: PcmFormat = PcmFormat::new(16000, 1);
+
+    /**
+    创建sapi5语音引擎。
+    `output_stream` 音频的输出端。
+    */
+    pub(crate) fn new(output_stream: Arc<dyn AudioSink>) -> Self {
         Self {
-            output_stream: BassChannelOutputStream::new(16000, 1),
+            output_stream,
             synth: Sapi5TtsSynthesizer::new(),
         }
     }
@@ -47,15 +57,31 @@ impl TtsEngine for Sapi5Engine {
                 first = false;
                 // 跳过开头的0.01秒，因为基本上他是静音的
                 // sapi5语音的采样率是每秒16000个样本，320个字节等于160个样本（0.01秒）
-                self.output_stream.put_data(&data[320..]);
+                self.output_stream.put_pcm(&data[320..], Self::FORMAT);
             } else {
-                self.output_stream.put_data(&data);
+                self.output_stream.put_pcm(&data, Self::FORMAT);
             }
         }
     }
 
+    async fn synth(&self, text: &str) -> Option<(Vec<u8>, PcmFormat)> {
+        let (tx, mut rx) = unbounded_channel();
+        self.synth.synth(text, move |data| tx.send(data).is_ok());
+        let mut result = vec![];
+        while let Some(data) = rx.recv().await {
+            result.extend_from_slice(&data);
+        }
+        // 和朗读时一样跳过开头的0.01秒
+        Some((result.get(320..).unwrap_or_default().to_vec(), Self::FORMAT))
+    }
+
+    async fn play_pcm(&self, data: &[u8], format: PcmFormat) {
+        self.output_stream.start();
+        self.output_stream.put_pcm(data, format);
+    }
+
     async fn wait(&self) {
-        self.output_stream.wait_until_stopped_or_stalled().await;
+        self.output_stream.wait().await;
     }
 
     fn stop(&self) {
@@ -11,9 +11,11 @@
  * See the License for the specific language governing permissions and limitations under the License.
  */
 
-use std::{str::FromStr, sync::Weak};
+use std::{
+    str::FromStr,
+    sync::{Arc, Weak},
+};
 
-use rigela_utils::bass::BassChannelOutputStream;
 #[cfg(target_arch = "x86")]
 use rigela_utils::ibmeci::Ibmeci;
 
@@ -21,7 +23,13 @@ use rigela_utils::ibmeci::Ibmeci;
 use crate::context::ContextAccessor;
 #[cfg(target_arch = "x86_64")]
 use crate::performer::tts::TtsProperty;
-use crate::{context::Context, performer::tts::TtsEngine};
+use crate::{
+    context::Context,
+    performer::{
+        audio::{AudioSink, PcmFormat},
+        tts::{EngineParam, TtsEngine},
+    },
+};
 
 //noinspection SpellCheckingInspection
 /// VVTTS语音库封装
@@ -30,12 +38,15 @@ pub(crate) struct VvttsEngine {
     context: Weak<Context>,
     #[cfg(target_arch = "x86")]
     eci: &'static Ibmeci,
-    output_stream: BassChannelOutputStream,
+    output_stream: Arc<dyn AudioSink>,
 }
 
 impl VvttsEngine {
+    /// ECI语音输出的音频格式
+    pub(crate) const FORMAT: PcmFormat = PcmFormat::new(11025, 1);
+
     #[allow(unused_variables)]
-    pub(crate) async fn new(context: Weak<Context>) -> Self {
+    pub(crate) async fn new(context: Weak<Context>, output_stream: Arc<dyn AudioSink>) -> Self {
         #[cfg(target_arch = "x86")]
         let eci = Ibmeci::get().await.unwrap();
         Self {
@@ -43,7 +54,7 @@ impl VvttsEngine {
             context,
             #[cfg(target_arch = "x86")]
             eci,
-            output_stream: BassChannelOutputStream::new(11025, 1),
+            output_stream,
         }
     }
 
@@ -66,6 +77,24 @@ impl VvttsEngine {
         proxy32.eci_set_voice_params(&params).await;
     }
 
+    // ECI语音特有的参数，性别0是男声，1是女声
+    fn get_eci_params() -> Vec<EngineParam> {
+        let param = |name: &str, label: String, max: i32, default: i32| EngineParam {
+            name: name.to_string(),
+            label,
+            min: 0,
+            max,
+            step: 1,
+            default,
+        };
+        vec![
+            param("gender", t!("tts.eci_gender").to_string(), 1, 0),
+            param("head_size", t!("tts.eci_head_size").to_string(), 100, 50),
+            param("roughness", t!("tts.eci_roughness").to_string(), 100, 0),
+            param("breathiness", t!("tts.eci_breathiness").to_string(), 100, 0),
+        ]
+    }
+
     fn convert_speed_param(value: i32) -> i32 {
         (value as f32 * 2.5) as i32
     }
@@ -90,11 +119,21 @@ impl TtsEngine for VvttsEngine {
             .await
             .eci_synth(text)
             .await;
-        self.output_stream.put_data(&data);
+        self.output_stream.put_pcm(&data, Self::FORMAT);
+    }
+
+    async fn synth(&self, text: &str) -> Option<(Vec<u8>, PcmFormat)> {
+        let proxy32 = self.context.get_proxy32process().await;
+        Some((proxy32.eci_synth(text).await, Self::FORMAT))
+    }
+
+    async fn play_pcm(&self, data: &[u8], format: PcmFormat) {
+        self.output_stream.start();
+        self.output_stream.put_pcm(data, format);
     }
 
     async fn wait(&self) {
-        self.output_stream.wait_until_stopped_or_stalled().await;
+        self.output_stream.wait().await;
     }
 
     fn stop(&self) {
@@ -136,6 +175,25 @@ impl TtsEngine for VvttsEngine {
             .eci_set_voice(u32::from_str(id.as_str()).unwrap_or(0))
             .await
     }
+
+    fn get_extra_params(&self) -> Vec<EngineParam> {
+        Self::get_eci_params()
+    }
+
+    async fn set_extra_param(&self, name: &str, value: i32) {
+        let proxy32 = self.context.get_proxy32process().await;
+        let mut params = proxy32.eci_get_voice_params().await;
+
+        match name {
+            "gender" => params.gender = value,
+            "head_size" => params.head_size = value,
+            "roughness" => params.roughness = value,
+            "breathiness" => params.breathiness = value,
+            _ => return,
+        }
+
+        proxy32.eci_set_voice_params(&params).await;
+    }
 }
 
 #[cfg(target_arch = "x86")]
@@ -145,11 +203,20 @@ impl TtsEngine for VvttsEngine {
     async fn speak(&self, text: &str) {
         self.output_stream.start();
         let data = self.eci.synth(text).await;
-        self.output_stream.put_data(&data);
+        self.output_stream.put_pcm(&data, Self::FORMAT);
+    }
+
+    async fn synth(&self, text: &str) -> Option<(Vec<u8>, PcmFormat)> {
+        Some((self.eci.synth(text).await, Self::FORMAT))
+    }
+
+    async fn play_pcm(&self, data: &[u8], format: PcmFormat) {
+        self.output_stream.start();
+        self.output_stream.put_pcm(data, format);
     }
 
     async fn wait(&self) {
-        self.output_stream.wait_until_stopped_or_stalled().await;
+        self.output_stream.wait().await;
     }
 
     fn stop(&self) {
@@ -190,6 +257,22 @@ impl TtsEngine for VvttsEngine {
     async fn set_voice(&self, id: String) {
         self.eci.set_voice(u32::from_str(id.as_str()).unwrap_or(0))
     }
+
+    fn get_extra_params(&self) -> Vec<EngineParam> {
+        Self::get_eci_params()
+    }
+
+    async fn set_extra_param(&self, name: &str, value: i32) {
+        use rigela_utils::ibmeci::{VP_BREATHINESS, VP_GENDER, VP_HEAD_SIZE, VP_ROUGHNESS};
+        let vp = match name {
+            "gender" => VP_GENDER,
+            "head_size" => VP_HEAD_SIZE,
+            "roughness" => VP_ROUGHNESS,
+            "breathiness" => VP_BREATHINESS,
+            _ => return,
+        };
+        self.eci.set_voice_param(vp, value);
+    }
 }
 
 unsafe impl Send for VvttsEngine {}
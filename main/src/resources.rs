@@ -35,8 +35,12 @@ impl ResourceProvider {
     pub(crate) fn new() -> Self {
         let root_dir = get_rigela_program_directory().join(PATH_NAME);
         if !root_dir.exists() {
-            if std::fs::create_dir_all(&root_dir).is_err() {
-                error!("创建资源目录失败");
+            if let Err(e) = std::fs::create_dir_all(&root_dir) {
+                error!(
+                    "Can't create the resource directory {}. {}",
+                    root_dir.display(),
+                    e
+                );
             }
         }
         Self { root_dir }
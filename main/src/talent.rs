@@ -11,6 +11,7 @@
  * See the License for the specific language governing permissions and limitations under the License.
  */
 
+mod history;
 pub(crate) mod mouse;
 mod navigator;
 mod program;
@@ -18,9 +19,9 @@ mod tts;
 
 use crate::{
     commander::{keyboard::combo_keys::ComboKey, CommandType},
-    configs::operations::get_hotkeys,
-    context::Context,
+    context::{Context, ContextAccessor},
     talent::{
+        history::{HistoryCopyTalent, HistoryCurrentTalent, HistoryNextTalent, HistoryPrevTalent},
         mouse::{ClickTalent, ReadMouseTalent, RightClickTalent},
         navigator::{
             ElementColorSetTalent, ElementCurrentLineTalent, ElementCurrentTalent,
@@ -28,13 +29,17 @@ use crate::{
             ModeNextTalent, ModePrevTalent,
         },
         program::{
-            CurrentCpuUsageTalent, CurrentDateTalent, CurrentTimeTalent, ExitTalent, HotkeysTalent,
-            PopupMenuTalent, StopTtsOutputTalent, ViewFocusTalent, ViewWindowTitleTalent,
+            CurrentCpuUsageTalent, CurrentDateTalent, CurrentProfileTalent, CurrentTimeTalent,
+            ExitTalent, HotkeysTalent, PopupMenuTalent, SayAllTalent, StopTtsOutputTalent,
+            ViewFocusTalent, ViewWindowTitleTalent,
         },
         tts::{
-            CacheToClipboardTalent, IncreaseTalent, MakeWordCacheCharTalent, NextCacheCharTalent,
-            NextPropTalent, PrevCacheCharTalent, PrevPropTalent, ReduceTalent,
-            TransCacheCharTalent,
+            CacheToClipboardTalent, CapitalModeTalent, IncreaseTalent, MakeWordCacheCharTalent,
+            NewVoiceProfileTalent, NextCacheCharTalent, NextCacheLineTalent,
+            NextCacheSentenceTalent, NextCacheWordTalent, NextPropTalent, NextVoiceProfileTalent,
+            NumberModeTalent, PrevCacheCharTalent, PrevCacheLineTalent, PrevCacheSentenceTalent,
+            PrevCacheWordTalent, PrevPropTalent, PrevVoiceProfileTalent, ReduceTalent,
+            SaveVoiceProfileTalent, SymbolLevelTalent, TransCacheCharTalent,
         },
     },
 };
@@ -127,6 +132,8 @@ impl TalentProvider {
             ViewFocusTalent,
             ViewWindowTitleTalent,
             StopTtsOutputTalent,
+            SayAllTalent,
+            CurrentProfileTalent,
             // 导航器能力
             ModePrevTalent,
             ModeNextTalent,
@@ -142,12 +149,30 @@ impl TalentProvider {
             ReduceTalent,
             NextPropTalent,
             PrevPropTalent,
+            SymbolLevelTalent,
+            NumberModeTalent,
+            CapitalModeTalent,
+            NextVoiceProfileTalent,
+            PrevVoiceProfileTalent,
+            SaveVoiceProfileTalent,
+            NewVoiceProfileTalent,
             // 语音缓冲区能力
             PrevCacheCharTalent,
             NextCacheCharTalent,
+            PrevCacheWordTalent,
+            NextCacheWordTalent,
+            PrevCacheSentenceTalent,
+            NextCacheSentenceTalent,
+            PrevCacheLineTalent,
+            NextCacheLineTalent,
             TransCacheCharTalent,
             MakeWordCacheCharTalent,
             CacheToClipboardTalent,
+            // 语音历史能力
+            HistoryPrevTalent,
+            HistoryNextTalent,
+            HistoryCurrentTalent,
+            HistoryCopyTalent,
             // 鼠标能力
             ClickTalent,
             RightClickTalent,
@@ -177,27 +202,25 @@ impl TalentProvider {
         self.talents.get(id).map(|t| t.clone())
     }
 
-    /// 更新自定义热键的能力映射
-    pub(crate) fn update_custom_combo_key_map(&self, context: Weak<Context>) {
-        let map = get_hotkeys(context);
-        let map2: HashMap<ComboKey, String>;
-        {
-            map2 = self
-                .combo_key_map
-                .lock()
-                .unwrap()
-                .iter()
-                .map(|(k, v)| {
-                    if map.contains_key(v) {
-                        (map.get(v).unwrap().clone(), v.clone())
-                    } else {
-                        (k.clone(), v.clone())
-                    }
-                })
-                .collect();
-        }
+    /// 获取全部能力的默认热键（能力ID -> 热键）
+    pub(crate) fn get_default_combo_keys(&self) -> HashMap<String, ComboKey> {
+        self.talents
+            .iter()
+            .filter_map(|(id, talent)| talent.get_combo_key().map(|key| (id.clone(), key)))
+            .collect()
+    }
 
-        *self.combo_key_map.lock().unwrap() = map2;
+    /// 更新自定义热键的能力映射，没有自定义热键的能力使用默认热键
+    pub(crate) fn update_custom_combo_key_map(&self, context: Weak<Context>) {
+        let hotkeys = context.get_config_manager().get_config().hotkeys_config;
+        let map: HashMap<ComboKey, String> = hotkeys
+            .get_keymap(&self.get_default_combo_keys())
+            .into_iter()
+            .filter(|(id, _)| self.talents.contains_key(id))
+            .map(|(id, key)| (key, id))
+            .collect();
+
+        *self.combo_key_map.lock().unwrap() = map;
     }
 
     /// 通过热键获取能力
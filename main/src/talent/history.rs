@@ -0,0 +1,78 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use crate::{
+    combo_key,
+    commander::keyboard::{
+        combo_keys::{ComboKey, State},
+        keys::Keys::*,
+        modify_keys::ModifierKeys,
+    },
+    configs::items::sound::SoundEvent::Boundary,
+    context::{Context, ContextAccessor},
+    performer::{history::HistoryEntry, sound::SoundArgument::Single},
+};
+use rigela_macros::talent;
+use rigela_utils::clip::set_clipboard_text;
+use std::sync::Weak;
+
+#[talent(doc = t!("history.prev_doc").to_string(), key = combo_key!("RigelA", VkOem4))]
+async fn history_prev(context: Weak<Context>) {
+    let Some(history) = context.get_performer().get_history() else {
+        return;
+    };
+    speak_entry(context, history.prev()).await;
+}
+
+#[talent(doc = t!("history.next_doc").to_string(), key = combo_key!("RigelA", VkOem6))]
+async fn history_next(context: Weak<Context>) {
+    let Some(history) = context.get_performer().get_history() else {
+        return;
+    };
+    speak_entry(context, history.next()).await;
+}
+
+#[talent(doc = t!("history.current_doc").to_string(), key = combo_key!("RigelA", VkOem5))]
+async fn history_current(context: Weak<Context>) {
+    let Some(history) = context.get_performer().get_history() else {
+        return;
+    };
+    speak_entry(context, history.current()).await;
+}
+
+#[talent(doc = t!("history.copy_doc").to_string(), key = combo_key!("RigelA_Shift", VkC))]
+async fn history_copy(context: Weak<Context>) {
+    let Some(history) = context.get_performer().get_history() else {
+        return;
+    };
+    let Some(entry) = history.current() else {
+        return;
+    };
+    set_clipboard_text(entry.text);
+    // 提示不记录到语音历史中，否则浏览的位置会被重置到这条提示上
+    context
+        .get_performer()
+        .speak_review(&t!("history.copied"))
+        .await;
+}
+
+// 重新朗读历史中的语音，已经到达历史的边界时播放提示音
+async fn speak_entry(context: Weak<Context>, entry: Option<HistoryEntry>) {
+    let Some(entry) = entry else {
+        context.get_performer().play_sound(Single(Boundary)).await;
+        return;
+    };
+
+    // 不再记录到语音历史中
+    context.get_performer().speak_review(&entry.text).await;
+}
@@ -18,7 +18,7 @@ use crate::{
         keys::Keys::*,
         modify_keys::ModifierKeys,
     },
-    configs::items::navigation::NavigationMode,
+    configs::items::{navigation::NavigationMode, sound::SoundEvent::Boundary},
     context::{Context, ContextAccessor},
     navigator::linear::LinearNavigator,
     performer::sound::SoundArgument::Single,
@@ -26,8 +26,6 @@ use crate::{
 use rigela_macros::talent;
 use std::sync::Weak;
 
-const WAVE: &str = "boundary.wav";
-
 #[talent(doc = t ! ("navigator.element_prev_doc").to_string(), key = combo_key ! (VkNumPad7))]
 async fn element_prev(context: Weak<Context>) {
     match context.get_ui_navigator().prev().await.current().await {
@@ -35,7 +33,7 @@ async fn element_prev(context: Weak<Context>) {
             context.get_performer().speak(element.as_ref()).await;
         }
         None => {
-            context.get_performer().play_sound(Single(WAVE)).await;
+            context.get_performer().play_sound(Single(Boundary)).await;
         }
     };
 }
@@ -46,7 +44,7 @@ async fn element_next(context: Weak<Context>) {
         Some(element) => {
             context.get_performer().speak(element.as_ref()).await;
         }
-        None => context.get_performer().play_sound(Single(WAVE)).await,
+        None => context.get_performer().play_sound(Single(Boundary)).await,
     };
 }
 
@@ -57,24 +55,24 @@ async fn element_current(context: Weak<Context>) {
             context.get_performer().speak(element.as_ref()).await;
         }
         None => {
-            context.get_performer().play_sound(Single(WAVE)).await;
+            context.get_performer().play_sound(Single(Boundary)).await;
         }
     };
 }
 
 #[talent(doc = t ! ("navigator.element_prev_line_doc").to_string(), key = combo_key ! (VkNumPad4))]
 async fn element_prev_line(context: Weak<Context>) {
-    context.get_performer().play_sound(Single(WAVE)).await;
+    context.get_performer().play_sound(Single(Boundary)).await;
 }
 
 #[talent(doc = t ! ("navigator.element_next_line_doc").to_string(), key = combo_key ! (VkNumPad6))]
 async fn element_next_line(context: Weak<Context>) {
-    context.get_performer().play_sound(Single(WAVE)).await;
+    context.get_performer().play_sound(Single(Boundary)).await;
 }
 
 #[talent(doc = t ! ("navigator.element_current_line_doc").to_string(), key = combo_key ! (VkNumPad5))]
 async fn element_current_line(context: Weak<Context>) {
-    context.get_performer().play_sound(Single(WAVE)).await;
+    context.get_performer().play_sound(Single(Boundary)).await;
 }
 
 #[talent(doc = t ! ("navigator.mode_next_doc").to_string(), key = combo_key ! (VkAdd))]
@@ -90,7 +88,7 @@ async fn mode_next(context: Weak<Context>) {
         NavigationMode::Linear => {
             let ctx = context.clone();
             context.get_work_runtime().spawn(async move {
-                ctx.get_performer().play_sound(Single(WAVE)).await;
+                ctx.get_performer().play_sound(Single(Boundary)).await;
             });
             t!("navigator.linear")
         }
@@ -116,7 +114,7 @@ async fn mode_prev(context: Weak<Context>) {
         NavigationMode::Tree => {
             let ctx = context.clone();
             context.get_work_runtime().spawn(async move {
-                ctx.get_performer().play_sound(Single(WAVE)).await;
+                ctx.get_performer().play_sound(Single(Boundary)).await;
             });
             t!("navigator.tree")
         }
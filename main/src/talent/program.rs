@@ -16,6 +16,7 @@ use crate::{
     commander::keyboard::{
         combo_keys::ComboKey, combo_keys::State, keys::Keys::*, modify_keys::ModifierKeys,
     },
+    configs::items::sound::SoundEvent,
     context::{Context, ContextAccessor},
     ext::window::AccessibleWindowExt,
     performer::{sound::SoundArgument::Single, Speakable},
@@ -102,7 +103,7 @@ async fn view_window_title(context: Weak<Context>) {
             );
             context
                 .get_performer()
-                .play_sound(Single("error.wav"))
+                .play_sound(Single(SoundEvent::Error))
                 .await
         }
     }
@@ -116,7 +117,37 @@ async fn view_focus(context: Weak<Context>) {
     context.get_performer().speak(&focused).await;
 }
 
+#[talent(doc = t ! ("program.say_all_doc").to_string(), key = combo_key ! ("RigelA", VkA))]
+async fn say_all(context: Weak<Context>) {
+    let Some(source) = context.get_event_core().editor.get_text_source() else {
+        context
+            .get_performer()
+            .speak(&t!("program.say_all_no_text"))
+            .await;
+        return;
+    };
+    let move_caret = context
+        .get_config_manager()
+        .get_config()
+        .general_config
+        .say_all_move_caret;
+    context
+        .get_performer()
+        .get_say_all()
+        .start(context.clone(), source, move_caret)
+        .await;
+}
+
+#[talent(doc = t ! ("program.current_profile_doc").to_string(), key = combo_key ! ("RigelA_Shift", VkP))]
+async fn current_profile(context: Weak<Context>) {
+    let msg = match context.get_config_manager().get_profile() {
+        Some(name) => t!("program.current_profile", value = name),
+        None => t!("program.default_profile"),
+    };
+    context.get_performer().speak(&msg).await;
+}
+
 #[talent(doc = t ! ("program.stop_tts_output_doc").to_string(), key = combo_key ! ("Ctrl", VkSpace))]
 async fn stop_tts_output(context: Weak<Context>) {
-    context.get_performer().get_tts().stop_all().await;
+    context.get_performer().stop().await;
 }
@@ -18,9 +18,11 @@ use crate::{
         keys::Keys::*,
         modify_keys::ModifierKeys,
     },
+    configs::items::tts::{CapitalConfig, NumberMode, SymbolLevel},
     context::{Context, ContextAccessor},
     performer::{
-        cache::Direction as CacheDirection,
+        cache::{CacheUnit, Direction as CacheDirection},
+        char_dict::CharDefinition,
         tts::{Direction, TtsProperty, ValueChange},
     },
 };
@@ -72,9 +74,7 @@ async fn prev_cache_char(context: Weak<Context>) {
         return;
     };
     let text = unsafe { &*cache.as_ptr() }.get(CacheDirection::Backward);
-    let tts = context.get_performer().get_tts();
-    tts.stop().await;
-    tts.speak(text).await;
+    context.get_performer().speak_review(&text).await;
 }
 
 #[talent(doc = t!("tts.next_cache_char_doc").to_string(), key = combo_key!("RigelA", VkRight))]
@@ -85,9 +85,37 @@ async fn next_cache_char(context: Weak<Context>) {
         return;
     };
     let text = unsafe { &*cache.as_ptr() }.get(CacheDirection::Forward);
-    let tts = context.get_performer().get_tts();
-    tts.stop().await;
-    tts.speak(text).await;
+    context.get_performer().speak_review(&text).await;
+}
+
+#[talent(doc = t!("tts.prev_cache_word_doc").to_string(), key = combo_key!("RigelA_Alt", VkLeft))]
+async fn prev_cache_word(context: Weak<Context>) {
+    speak_cache_unit(context, CacheUnit::Word, CacheDirection::Backward).await;
+}
+
+#[talent(doc = t!("tts.next_cache_word_doc").to_string(), key = combo_key!("RigelA_Alt", VkRight))]
+async fn next_cache_word(context: Weak<Context>) {
+    speak_cache_unit(context, CacheUnit::Word, CacheDirection::Forward).await;
+}
+
+#[talent(doc = t!("tts.prev_cache_sentence_doc").to_string(), key = combo_key!("RigelA_Shift", VkLeft))]
+async fn prev_cache_sentence(context: Weak<Context>) {
+    speak_cache_unit(context, CacheUnit::Sentence, CacheDirection::Backward).await;
+}
+
+#[talent(doc = t!("tts.next_cache_sentence_doc").to_string(), key = combo_key!("RigelA_Shift", VkRight))]
+async fn next_cache_sentence(context: Weak<Context>) {
+    speak_cache_unit(context, CacheUnit::Sentence, CacheDirection::Forward).await;
+}
+
+#[talent(doc = t!("tts.prev_cache_line_doc").to_string(), key = combo_key!("RigelA_Alt", VkUp))]
+async fn prev_cache_line(context: Weak<Context>) {
+    speak_cache_unit(context, CacheUnit::Line, CacheDirection::Backward).await;
+}
+
+#[talent(doc = t!("tts.next_cache_line_doc").to_string(), key = combo_key!("RigelA_Alt", VkDown))]
+async fn next_cache_line(context: Weak<Context>) {
+    speak_cache_unit(context, CacheUnit::Line, CacheDirection::Forward).await;
 }
 
 #[talent(doc = t!("tts.trans_cache_char_doc").to_string(), key = combo_key!("RigelA", VkUp))]
@@ -98,11 +126,24 @@ async fn trans_cache_char(context: Weak<Context>) {
         return;
     };
     let text = unsafe { &*cache.as_ptr() }.get(CacheDirection::Current);
-    // Todo: 查字典
+    let performer = context.get_performer();
+    let (Some(dict), Some(ch)) = (performer.get_char_dict(), text.chars().next()) else {
+        performer.speak_review(&text).await;
+        return;
+    };
 
-    let tts = context.get_performer().get_tts();
-    tts.stop().await;
-    tts.speak(text).await;
+    // 查字典，连续按下时依次朗读每一条解释
+    match dict.lookup(ch) {
+        CharDefinition::Text(text) => performer.speak_review(&text).await,
+        CharDefinition::CodePoint(code) => {
+            // 码位需要逐个字符朗读，否则其中的数字可能会被读成数值
+            let ssml = format!(
+                r#"U+<say-as interpret-as="characters">{:04X}</say-as>"#,
+                code
+            );
+            performer.speak_review_ssml(&ssml).await
+        }
+    };
 }
 
 #[talent(doc = t!("tts.make_word_cache_char_doc").to_string(), key = combo_key!("RigelA", VkDown))]
@@ -112,10 +153,8 @@ async fn make_word_cache_char(context: Weak<Context>) {
     let Some(cache) = context.get_performer().get_cache() else {
         return;
     };
-    let tts = context.get_performer().get_tts();
-    tts.stop().await;
     let words = unsafe { &*cache.as_ptr() }.get_current_char_words();
-    tts.speak(words).await;
+    context.get_performer().speak_review(&words).await;
 }
 
 #[talent(doc = t!("tts.cache_to_clip_doc").to_string(), key = combo_key!("RigelA", VkC))]
@@ -128,14 +167,138 @@ async fn cache_to_clipboard(context: Weak<Context>) {
     // context.performer.play_sound("boundary.wav").await;
 }
 
+#[talent(doc = t!("tts.symbol_level_doc").to_string(), key = combo_key!("RigelA", VkP))]
+async fn symbol_level(context: Weak<Context>) {
+    let mut root = context.get_config_manager().get_config();
+    let level = match root.tts_config.symbol_level {
+        SymbolLevel::Off => SymbolLevel::Low,
+        SymbolLevel::Low => SymbolLevel::Most,
+        SymbolLevel::Most => SymbolLevel::All,
+        // 字符级别只用于逐字朗读，朗读句子时会读出每一个空格和换行
+        SymbolLevel::All | SymbolLevel::Character => SymbolLevel::Off,
+    };
+    root.tts_config.symbol_level = level;
+    context.get_config_manager().set_config(&root);
+
+    let info = match level {
+        SymbolLevel::Off => t!("tts.symbol_level_off"),
+        SymbolLevel::Low => t!("tts.symbol_level_low"),
+        SymbolLevel::Most => t!("tts.symbol_level_most"),
+        SymbolLevel::All | SymbolLevel::Character => t!("tts.symbol_level_all"),
+    };
+    context.get_performer().speak(&info).await;
+}
+
+#[talent(doc = t!("tts.next_profile_doc").to_string(), key = combo_key!("RigelA", VkV))]
+async fn next_voice_profile(context: Weak<Context>) {
+    switch_voice_profile(context, Direction::Next).await;
+}
+
+#[talent(doc = t!("tts.prev_profile_doc").to_string(), key = combo_key!("RigelA_Shift", VkV))]
+async fn prev_voice_profile(context: Weak<Context>) {
+    switch_voice_profile(context, Direction::Prev).await;
+}
+
+#[talent(doc = t!("tts.save_profile_doc").to_string(), key = combo_key!("RigelA_Alt", VkV))]
+async fn save_voice_profile(context: Weak<Context>) {
+    let name = context.get_performer().get_tts().save_voice_profile(false);
+    let info = t!("tts.profile_saved", value = name);
+    context.get_performer().speak(&info).await;
+}
+
+#[talent(doc = t!("tts.new_profile_doc").to_string(), key = combo_key!("RigelA_Ctrl", VkV))]
+async fn new_voice_profile(context: Weak<Context>) {
+    let name = context.get_performer().get_tts().save_voice_profile(true);
+    let info = t!("tts.profile_saved", value = name);
+    context.get_performer().speak(&info).await;
+}
+
+#[talent(doc = t!("tts.number_mode_doc").to_string(), key = combo_key!("RigelA", VkN))]
+async fn number_mode(context: Weak<Context>) {
+    let mut root = context.get_config_manager().get_config();
+    let mode = match root.tts_config.number_mode {
+        NumberMode::Engine => NumberMode::Digits,
+        NumberMode::Digits => NumberMode::Full,
+        NumberMode::Full => NumberMode::Engine,
+    };
+    root.tts_config.number_mode = mode;
+    context.get_config_manager().set_config(&root);
+
+    let info = match mode {
+        NumberMode::Engine => t!("tts.number_mode_engine"),
+        NumberMode::Digits => t!("tts.number_mode_digits"),
+        NumberMode::Full => t!("tts.number_mode_full"),
+    };
+    context.get_performer().speak(&info).await;
+}
+
+#[talent(doc = t!("tts.capital_mode_doc").to_string(), key = combo_key!("RigelA_Alt", VkC))]
+async fn capital_mode(context: Weak<Context>) {
+    let mut root = context.get_config_manager().get_config();
+    let capital = &mut root.tts_config.capital;
+    // 依次切换：提高音调、提高音调并播放音效、朗读“大写”、播放音效、不提示
+    let raised = capital.pitch_change != 0;
+    let (pitch, prefix, sound) = match (raised, capital.say_prefix, capital.play_sound) {
+        (true, false, false) => (true, false, true),
+        (true, false, true) => (false, true, false),
+        (false, true, false) => (false, false, true),
+        (false, false, true) => (false, false, false),
+        _ => (true, false, false),
+    };
+    capital.pitch_change = match (pitch, raised) {
+        (true, true) => capital.pitch_change,
+        (true, false) => CapitalConfig::default().pitch_change,
+        (false, _) => 0,
+    };
+    capital.say_prefix = prefix;
+    capital.play_sound = sound;
+    context.get_config_manager().set_config(&root);
+
+    let info = match (pitch, prefix, sound) {
+        (true, false, false) => t!("tts.capital_mode_pitch"),
+        (true, false, true) => t!("tts.capital_mode_pitch_sound"),
+        (false, true, false) => t!("tts.capital_mode_prefix"),
+        (false, false, true) => t!("tts.capital_mode_sound"),
+        _ => t!("tts.capital_mode_none"),
+    };
+    context.get_performer().speak(&info).await;
+}
+
+// 按照指定的单位在缓冲区中移动并朗读
+async fn speak_cache_unit(context: Weak<Context>, unit: CacheUnit, direction: CacheDirection) {
+    cancel_edge_handle!(context);
+
+    let Some(cache) = context.get_performer().get_cache() else {
+        return;
+    };
+    let text = unsafe { &*cache.as_ptr() }.get_unit(unit, direction);
+    if text.is_empty() {
+        return;
+    }
+    context.get_performer().speak_review(&text).await;
+}
+
+async fn switch_voice_profile(context: Weak<Context>, direction: Direction) {
+    let tts = context.get_performer().get_tts();
+    let info = match tts.switch_voice_profile(direction).await {
+        Some(name) => t!("tts.profile_info", value = name),
+        None => t!("tts.profile_none"),
+    };
+    context.get_performer().speak(&info).await;
+}
+
 async fn speak_tts_prop(context: Weak<Context>) {
     let tts = context.get_performer().get_tts();
 
-    let info = match tts.get_tts_prop_value(None).await {
+    let Some(property) = tts.get_tts_prop_value(None).await else {
+        return;
+    };
+    let info = match property {
         TtsProperty::Speed(v) => t!("tts.speed_info", value = v),
         TtsProperty::Pitch(v) => t!("tts.pitch_info", value = v),
         TtsProperty::Volume(v) => t!("tts.volume_info", value = v),
         TtsProperty::Voice(v) => t!("tts.role", value = format!("{}_{}", v.engine, v.name)),
+        TtsProperty::Extra(p, v) => t!("tts.extra_param_info", name = p.label, value = v),
     };
     context.get_performer().speak(&info).await;
 }
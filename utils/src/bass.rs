@@ -158,6 +158,16 @@ macro_rules! bass {
             $flags
         )
     };
+    ($module:expr,channel_set_attribute,$handle:expr,$attrib:expr,$value:expr) => {
+        call_proc!(
+            $module,
+            BASS_ChannelSetAttribute,
+            extern "system" fn(i32, i32, f32) -> bool,
+            $handle,
+            $attrib,
+            $value
+        )
+    };
     ($module:expr,channel_slide_attribute,$handle:expr,$attrib:expr,$value:expr,$time:expr) => {
         call_proc!(
             $module,
@@ -238,9 +248,7 @@ const BASS_SYNC_ONETIME: u32 = 0x80000000; // flag: sync only once, else continu
 
 // Channel attributes
 const BASS_ATTRIB_FREQ: i32 = 1;
-#[allow(unused)]
 const BASS_ATTRIB_VOL: i32 = 2;
-#[allow(unused)]
 const BASS_ATTRIB_PAN: i32 = 3;
 //noinspection SpellCheckingInspection
 #[allow(unused)]
@@ -460,6 +468,36 @@ impl BassChannelOutputStream {
         );
     }
 
+    //noinspection StructuralWrap
+    /**
+    设置播放音量。
+    `value` 音量，0是静音，1是原始音量。
+    */
+    pub fn set_volume(&self, value: f32) {
+        bass!(
+            self.h_module,
+            channel_set_attribute,
+            self.h_bass,
+            BASS_ATTRIB_VOL,
+            value
+        );
+    }
+
+    //noinspection StructuralWrap
+    /**
+    设置声道平衡。
+    `value` 平衡，-1是左声道，0是居中，1是右声道。
+    */
+    pub fn set_pan(&self, value: f32) {
+        bass!(
+            self.h_module,
+            channel_set_attribute,
+            self.h_bass,
+            BASS_ATTRIB_PAN,
+            value
+        );
+    }
+
     /**
     检查样本、流或MOD音乐是否处于活动状态（正在播放）或暂停状态。还可以检查是否正在录制。
     */
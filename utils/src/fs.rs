@@ -11,10 +11,14 @@
  * See the License for the specific language governing permissions and limitations under the License.
  */
 
+use log::error;
 use std::{
-    fs::create_dir,
-    io::Error,
+    env::{args_os, current_exe, set_var, temp_dir, var_os},
+    ffi::{OsStr, OsString},
+    fs::create_dir_all,
+    io::{Error, ErrorKind},
     path::{Path, PathBuf},
+    sync::OnceLock,
 };
 use tokio::{
     fs::{metadata, OpenOptions},
@@ -24,16 +28,135 @@ use win_wrap::shell::{get_known_folder_path, FOLDERID_Profile, KF_FLAG_DEFAULT};
 
 pub const DIR_NAME: &str = ".rigela";
 
-/// 获取程序存储目录
+/// 指定数据目录的环境变量，由命令行参数指定数据目录时，也会设置这个变量，让子进程使用同样的目录
+pub const DATA_DIR_ENV: &str = "RIGELA_DATA_DIR";
+
+/// 指定数据目录的命令行参数，例如`--data-dir D:\rigela`或者`--data-dir=D:\rigela`
+pub const DATA_DIR_ARG: &str = "--data-dir";
+
+/// 便携模式的标记文件，程序所在的目录中存在这个文件时，数据保存在程序所在的目录中
+pub const PORTABLE_MARKER: &str = "portable";
+
+static DATA_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+// 无法使用数据目录的原因，这时数据目录是临时目录
+static DATA_DIR_ERROR: OnceLock<String> = OnceLock::new();
+
+/**
+ 获取程序存储目录（数据目录），配置、日志、动态库和资源都保存在这个目录中。
+ 如果无法使用数据目录，会记录错误并使用临时目录，不会让程序崩溃，此后一直使用这个临时目录；需要处理错误的地方请使用get_rigela_data_directory。
+ */
 pub fn get_rigela_program_directory() -> PathBuf {
-    let home_path = get_known_folder_path(&FOLDERID_Profile, KF_FLAG_DEFAULT, None).unwrap();
-    let program_dir = Path::new(&home_path).join(DIR_NAME);
+    get_rigela_data_directory().unwrap_or_else(|e| {
+        DATA_DIR
+            .get_or_init(|| {
+                let dir = temp_dir().join(DIR_NAME);
+                error!(
+                    "Can't use the data directory, using {} instead. {}",
+                    dir.display(),
+                    e
+                );
+                if let Err(e) = create_dir_all(&dir) {
+                    error!(
+                        "Can't create the temporary data directory {}. {}",
+                        dir.display(),
+                        e
+                    );
+                }
+                DATA_DIR_ERROR.get_or_init(|| e.to_string());
+                dir
+            })
+            .clone()
+    })
+}
+
+/**
+ 获取无法使用数据目录的原因，返回None表示数据目录可以正常使用。
+ 返回错误时数据保存在临时目录中，程序退出后可能会丢失，启动时应该提示用户。
+ */
+pub fn get_data_directory_error() -> Option<&'static str> {
+    DATA_DIR_ERROR.get().map(String::as_str)
+}
+
+/**
+ 解析并创建数据目录，按照以下的顺序查找，找到后不再改变：
+ 1. 命令行参数--data-dir；
+ 2. 环境变量RIGELA_DATA_DIR；
+ 3. 便携模式，程序所在的目录中存在portable文件时，使用程序所在的目录；
+ 4. 用户目录中的.rigela文件夹。
+ */
+pub fn get_rigela_data_directory() -> Result<PathBuf, Error> {
+    if let Some(e) = DATA_DIR_ERROR.get() {
+        return Err(Error::other(e.clone()));
+    }
+    if let Some(dir) = DATA_DIR.get() {
+        return Ok(dir.clone());
+    }
+
+    let dir = resolve_data_directory()?;
+    create_dir_all(&dir).map_err(|e| {
+        Error::new(
+            e.kind(),
+            format!("Can't create the data directory {}. {}", dir.display(), e),
+        )
+    })?;
 
-    if !program_dir.exists() {
-        create_dir(&program_dir).expect("Can't create the root directory.");
+    Ok(DATA_DIR.get_or_init(|| dir).clone())
+}
+
+/**
+ 解析数据目录，并把结果写入环境变量RIGELA_DATA_DIR，代理进程等子进程会继承环境变量，这样他们和主程序使用同一个数据目录。
+ 其他线程读取环境变量的同时修改环境变量是不安全的，所以必须在main函数的开头、创建运行时和任何线程之前调用；子进程不需要调用。
+ */
+pub fn init_data_directory() {
+    let dir = get_rigela_program_directory();
+    if var_os(DATA_DIR_ENV).as_deref() != Some(dir.as_os_str()) {
+        set_var(DATA_DIR_ENV, dir);
+    }
+}
+
+// 按照优先级查找数据目录
+fn resolve_data_directory() -> Result<PathBuf, Error> {
+    if let Some(dir) = get_data_dir_arg(args_os()) {
+        return Ok(dir);
+    }
+    if let Some(dir) = var_os(DATA_DIR_ENV).filter(|v| !v.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    if let Some(dir) = current_exe().ok().as_deref().and_then(Path::parent) {
+        if dir.join(PORTABLE_MARKER).exists() {
+            return Ok(dir.to_path_buf());
+        }
     }
 
-    program_dir
+    let home_path =
+        get_known_folder_path(&FOLDERID_Profile, KF_FLAG_DEFAULT, None).map_err(|e| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("Can't get the user profile. {}", e),
+            )
+        })?;
+    Ok(Path::new(&home_path).join(DIR_NAME))
+}
+
+// 从命令行参数中获取数据目录，参数不一定是有效的Unicode，所以按照字节匹配
+fn get_data_dir_arg(args: impl Iterator<Item = OsString>) -> Option<PathBuf> {
+    let mut args = args.skip(1);
+    while let Some(arg) = args.next() {
+        if arg == DATA_DIR_ARG {
+            return args.next().filter(|a| !a.is_empty()).map(PathBuf::from);
+        }
+        if let Some(dir) = arg
+            .as_encoded_bytes()
+            .strip_prefix(DATA_DIR_ARG.as_bytes())
+            .and_then(|a| a.strip_prefix(b"="))
+        {
+            // 在ASCII字符之后分割，得到的字节仍然是有效的编码
+            let dir = unsafe { OsStr::from_encoded_bytes_unchecked(dir) };
+            return (!dir.is_empty()).then(|| PathBuf::from(dir));
+        }
+    }
+    None
 }
 
 /**
@@ -83,3 +206,29 @@ pub async fn read_file(path: &PathBuf) -> Result<String, Error> {
         .await?;
     Ok(result)
 }
+
+#[cfg(test)]
+mod test_fs {
+    use crate::fs::get_data_dir_arg;
+    use std::{ffi::OsString, path::PathBuf};
+
+    fn parse(args: &[&str]) -> Option<PathBuf> {
+        get_data_dir_arg(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn main() {
+        assert_eq!(
+            parse(&["rigela.exe", "--data-dir", "D:\\rigela"]),
+            Some(PathBuf::from("D:\\rigela"))
+        );
+        assert_eq!(
+            parse(&["rigela.exe", "--data-dir=D:\\rigela"]),
+            Some(PathBuf::from("D:\\rigela"))
+        );
+        assert_eq!(parse(&["rigela.exe"]), None);
+        assert_eq!(parse(&["rigela.exe", "--data-dir"]), None);
+        assert_eq!(parse(&["rigela.exe", "--data-dir="]), None);
+        assert_eq!(parse(&["--data-dir=D:\\rigela"]), None);
+    }
+}
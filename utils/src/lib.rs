@@ -34,6 +34,8 @@ pub mod logger;
 pub mod pipe;
 #[cfg(feature = "screen")]
 pub mod screen;
+#[cfg(feature = "wav")]
+pub mod wav;
 
 //noinspection HttpUrlsUsage
 pub const SERVER_HOME_URI: &str = "http://rigela.site/rigela";
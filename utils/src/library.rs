@@ -14,18 +14,24 @@
 use crate::fs::get_rigela_program_directory;
 use log::error;
 use std::{
-    fs::{create_dir, OpenOptions},
+    fs::{create_dir_all, OpenOptions},
     io::Write,
     path::PathBuf,
 };
 
 /**
- 获取RigelA库目录的路径。
+ 获取RigelA库目录的路径，库目录位于数据目录中。
  */
 pub fn get_rigela_library_path() -> PathBuf {
     let path = get_rigela_program_directory().join("libs");
     if !path.exists() {
-        create_dir(&path).unwrap();
+        if let Err(e) = create_dir_all(&path) {
+            error!(
+                "Can't create the library directory {}. {}",
+                path.display(),
+                e
+            );
+        }
     }
     path
 }
@@ -11,8 +11,8 @@
  * See the License for the specific language governing permissions and limitations under the License.
  */
 
-use crate::fs::get_rigela_program_directory;
-use log::LevelFilter;
+use crate::fs::get_rigela_data_directory;
+use log::{error, info, LevelFilter};
 use log4rs::{
     append::{
         console::{ConsoleAppender, Target},
@@ -33,13 +33,12 @@ const LOG_FILE_NAME: &str = "run.log";
 
 /**
 初始化日志收集器。
-`path` 日志文件存放的文件夹路径，是相对于本项目用户目录的路径。
+如果无法使用数据目录或者无法创建日志文件，日志只输出到标准错误，错误会在日志初始化以后记录下来。
+`path` 日志文件存放的文件夹路径，是相对于本项目数据目录的路径。
 */
 pub fn init_logger(path: Option<&str>) {
     let level = LevelFilter::Info;
-    let file_path = get_rigela_program_directory()
-        .join("logs")
-        .join(path.unwrap_or(LOG_FILE_NAME));
+    let data_dir = get_rigela_data_directory();
 
     // 创建一个标准错误日志器
     let stderr = ConsoleAppender::builder()
@@ -49,32 +48,53 @@ pub fn init_logger(path: Option<&str>) {
         .build();
 
     //输出到文件
-    let logfile = RollingFileAppender::builder()
-        .build(
-            file_path,
-            Box::new(CompoundPolicy::new(
-                Box::new(SizeTrigger::new(1024 * 1024)), // 超过1MB后滚动
-                Box::new(DeleteRoller::new()),
-            )),
-        )
-        .unwrap();
+    let logfile = data_dir
+        .as_ref()
+        .map_err(|e| e.to_string())
+        .and_then(|dir| {
+            RollingFileAppender::builder()
+                .build(
+                    dir.join("logs").join(path.unwrap_or(LOG_FILE_NAME)),
+                    Box::new(CompoundPolicy::new(
+                        Box::new(SizeTrigger::new(1024 * 1024)), // 超过1MB后滚动
+                        Box::new(DeleteRoller::new()),
+                    )),
+                )
+                .map_err(|e| format!("Can't create the log file. {}", e))
+        });
 
     // 将跟踪级别输出记录到文件中，其中跟踪是默认级别，以编程方式指定的级别记录到stderr。
-    let config = Config::builder()
-        .appender(Appender::builder().build("logfile", Box::new(logfile)))
-        .appender(
-            Appender::builder()
-                .filter(Box::new(ThresholdFilter::new(level)))
-                .build("stderr", Box::new(stderr)),
-        )
-        .build(
-            Root::builder()
-                .appender("logfile")
-                .appender("stderr")
-                .build(LevelFilter::Trace),
-        )
-        .unwrap();
+    let mut config = Config::builder().appender(
+        Appender::builder()
+            .filter(Box::new(ThresholdFilter::new(level)))
+            .build("stderr", Box::new(stderr)),
+    );
+    let mut root = Root::builder().appender("stderr");
+    let file_error = match logfile {
+        Ok(logfile) => {
+            config = config.appender(Appender::builder().build("logfile", Box::new(logfile)));
+            root = root.appender("logfile");
+            None
+        }
+        Err(e) => Some(e),
+    };
+    let config = match config.build(root.build(LevelFilter::Trace)) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Can't build the logger config. {}", e);
+            return;
+        }
+    };
 
     // 使用此选项可以在运行时更改日志级别。这意味着您可以更改默认日志级别以进行跟踪，如果您正在尝试调试某个问题，并且需要打开更多日志，则在完成后将其关闭。
-    init_config(config).expect("Can't initialize the logger.");
+    if let Err(e) = init_config(config) {
+        eprintln!("Can't initialize the logger. {}", e);
+        return;
+    }
+
+    match (file_error, data_dir) {
+        (Some(e), _) => error!("The log is only written to stderr. {}", e),
+        (None, Ok(dir)) => info!("The data directory is {}.", dir.display()),
+        (None, Err(_)) => {}
+    }
 }
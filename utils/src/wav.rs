@@ -0,0 +1,267 @@
+/*
+ * Copyright (c) 2024. The RigelA open source project team and
+ * its contributors reserve all rights.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ * http://www.apache.org/licenses/LICENSE-2.0
+ * Unless required by applicable law or agreed to in writing, software distributed under the
+ * License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and limitations under the License.
+ */
+
+use log::error;
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufWriter, Error, Result, Seek, SeekFrom, Write},
+    path::PathBuf,
+};
+
+/// 每写入这么多字节的数据更新一次文件头，程序意外退出时，最多有这么多数据没有计入文件头中的长度
+const HEADER_UPDATE_BYTES: u32 = 1024 * 1024;
+
+/// WAV文件中音频数据的最大字节数，文件头中的RIFF长度包含36字节的头部，也不能超过u32的范围
+const MAX_DATA_LENGTH: u32 = u32::MAX - 36;
+
+/// PCM音频的格式，样本都是16位的有符号整数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PcmFormat {
+    pub sample_rate: u32,
+    pub channels: u32,
+}
+
+impl PcmFormat {
+    pub const fn new(sample_rate: u32, channels: u32) -> Self {
+        Self {
+            sample_rate,
+            channels,
+        }
+    }
+}
+
+/**
+把PCM音频写入WAV文件，文件在第一次写入数据时创建，此后写入的数据都追加到文件中。
+文件只能使用一种格式，和第一次写入的格式不同的数据会被丢弃。
+文件头中的长度每隔一段数据更新一次，调用finish或者销毁写入器时会写入完整的长度。
+*/
+#[derive(Debug)]
+pub struct WavWriter {
+    path: PathBuf,
+    file: Option<BufWriter<File>>,
+    format: Option<PcmFormat>,
+    length: u32,
+    // 文件头中已经写入的长度
+    header_length: u32,
+}
+
+impl WavWriter {
+    /**
+    创建WAV文件的写入器。
+    `path` 文件的路径，已经存在的文件会被覆盖。
+    */
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            file: None,
+            format: None,
+            length: 0,
+            header_length: 0,
+        }
+    }
+
+    /**
+    获取文件的路径。
+    */
+    pub fn get_path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /**
+    写入PCM数据，数据超过WAV文件能保存的长度时返回错误。
+    `data` 音频数据。
+    `format` 音频的格式。
+    */
+    pub fn write(&mut self, data: &[u8], format: PcmFormat) -> Result<()> {
+        if self.file.is_none() {
+            let file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .truncate(true)
+                .open(&self.path)?;
+            let mut file = BufWriter::new(file);
+            file.write_all(&wav_header(format, 0))?;
+            self.file = Some(file);
+            self.format = Some(format);
+        }
+        if self.format != Some(format) {
+            error!(
+                "Discard the audio data in a different format ({:?}) from the wav file.",
+                format
+            );
+            return Ok(());
+        }
+
+        let length = u32::try_from(data.len())
+            .ok()
+            .and_then(|n| self.length.checked_add(n))
+            .filter(|n| *n <= MAX_DATA_LENGTH)
+            .ok_or_else(|| Error::other("The audio data is too large for the wav file."))?;
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        file.write_all(data)?;
+        self.length = length;
+        if self.length - self.header_length >= HEADER_UPDATE_BYTES {
+            self.update_header()?;
+        }
+
+        Ok(())
+    }
+
+    /**
+    把缓冲的数据和完整的长度写入文件，此后还可以继续写入数据。
+    */
+    pub fn finish(&mut self) -> Result<()> {
+        self.update_header()?;
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+
+    // 更新文件头中的长度，然后回到文件末尾继续写入
+    fn update_header(&mut self) -> Result<()> {
+        let (Some(file), Some(format)) = (self.file.as_mut(), self.format) else {
+            return Ok(());
+        };
+        if self.header_length == self.length {
+            return Ok(());
+        }
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(&wav_header(format, self.length))?;
+        file.seek(SeekFrom::End(0))?;
+        self.header_length = self.length;
+
+        Ok(())
+    }
+}
+
+impl Drop for WavWriter {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            error!("Can't finish the wav file ({}). {}", self.path.display(), e);
+        }
+    }
+}
+
+/**
+保存在内存中的PCM音频，只记录最后写入的数据的格式。
+*/
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PcmBuffer {
+    format: Option<PcmFormat>,
+    data: Vec<u8>,
+}
+
+impl PcmBuffer {
+    /**
+    追加PCM数据。
+    `data` 音频数据。
+    `format` 音频的格式。
+    */
+    pub fn append(&mut self, data: &[u8], format: PcmFormat) {
+        self.format = Some(format);
+        self.data.extend_from_slice(data);
+    }
+
+    /**
+    清空所有的数据。
+    */
+    pub fn clear(&mut self) {
+        self.format = None;
+        self.data.clear();
+    }
+
+    /**
+    获取音频的格式和数据。
+    */
+    pub fn get_data(&self) -> (Option<PcmFormat>, Vec<u8>) {
+        (self.format, self.data.clone())
+    }
+}
+
+// 生成WAV文件头，`length`是音频数据的字节数
+fn wav_header(format: PcmFormat, length: u32) -> [u8; 44] {
+    let block_align = format.channels * 2;
+    let mut header = [0u8; 44];
+    header[0..4].copy_from_slice(b"RIFF");
+    header[4..8].copy_from_slice(&(36 + length).to_le_bytes());
+    header[8..16].copy_from_slice(b"WAVEfmt ");
+    header[16..20].copy_from_slice(&16u32.to_le_bytes());
+    // PCM格式
+    header[20..22].copy_from_slice(&1u16.to_le_bytes());
+    header[22..24].copy_from_slice(&(format.channels as u16).to_le_bytes());
+    header[24..28].copy_from_slice(&format.sample_rate.to_le_bytes());
+    header[28..32].copy_from_slice(&(format.sample_rate * block_align).to_le_bytes());
+    header[32..34].copy_from_slice(&(block_align as u16).to_le_bytes());
+    header[34..36].copy_from_slice(&16u16.to_le_bytes());
+    header[36..40].copy_from_slice(b"data");
+    header[40..44].copy_from_slice(&length.to_le_bytes());
+    header
+}
+
+#[cfg(test)]
+mod test_wav {
+    use crate::wav::{PcmBuffer, PcmFormat, WavWriter};
+
+    #[test]
+    fn test_pcm_buffer() {
+        let format = PcmFormat::new(16000, 1);
+        let mut buffer = PcmBuffer::default();
+        buffer.append(&[1, 2], format);
+        buffer.append(&[3, 4], format);
+        assert_eq!(buffer.get_data(), (Some(format), vec![1, 2, 3, 4]));
+
+        buffer.clear();
+        assert_eq!(buffer.get_data(), (None, vec![]));
+    }
+
+    #[test]
+    fn test_wav_writer() {
+        let path = std::env::temp_dir().join(format!("rigela-wav-{}.wav", std::process::id()));
+        let format = PcmFormat::new(11025, 2);
+        let mut writer = WavWriter::new(path.clone());
+        writer.write(&[1, 2, 3, 4], format).unwrap();
+        writer.write(&[5, 6, 7, 8], format).unwrap();
+        writer.write(&[9, 9], PcmFormat::new(16000, 1)).unwrap();
+        writer.finish().unwrap();
+
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(data.len(), 52);
+        assert_eq!(&data[..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(data[4..8].try_into().unwrap()), 44);
+        assert_eq!(u16::from_le_bytes(data[22..24].try_into().unwrap()), 2);
+        assert_eq!(u32::from_le_bytes(data[24..28].try_into().unwrap()), 11025);
+        assert_eq!(u32::from_le_bytes(data[28..32].try_into().unwrap()), 44100);
+        assert_eq!(u32::from_le_bytes(data[40..44].try_into().unwrap()), 8);
+        assert_eq!(&data[44..], &[1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_wav_writer_overflow() {
+        let path = std::env::temp_dir().join(format!("rigela-wav-{}-big.wav", std::process::id()));
+        let format = PcmFormat::new(16000, 1);
+        let mut writer = WavWriter::new(path.clone());
+        writer.write(&[1, 2], format).unwrap();
+        // 模拟已经写入了接近上限的数据
+        writer.length = u32::MAX - 38;
+        assert!(writer.write(&[3, 4, 5, 6], format).is_err());
+        writer.write(&[3, 4], format).unwrap();
+        assert_eq!(writer.length, u32::MAX - 36);
+
+        drop(writer);
+        std::fs::remove_file(&path).unwrap();
+    }
+}